@@ -0,0 +1,210 @@
+use super::structures::ExecuteOutput;
+use crate::parse::structures::Numeric;
+
+// Arrays nested to the same lengths at every level are treated as n-dimensional, e.g.
+// [[1, 2, 3], [4, 5, 6]] has shape [2, 3]. Ragged arrays, and arrays of maps, only have a shape
+// as far down as they are rectangular, so [[1], [2, 3]] has shape [2]
+
+// Shape vector of a value: the length of each axis of an array, the number of keys of a map,
+// and an empty vector for a single value
+pub fn execute_shape(rhs: ExecuteOutput) -> ExecuteOutput {
+    let shape = match &rhs {
+        ExecuteOutput::Map (dict) => vec![dict.len()],
+        other => array_shape(other)
+    };
+
+    shape_to_output(&shape)
+}
+
+// Number of items along the first axis, or keys of a map. A single value counts as 1
+pub fn execute_tally(rhs: ExecuteOutput) -> ExecuteOutput {
+    let tally = match rhs {
+        ExecuteOutput::Array (arr) => arr.len(),
+        ExecuteOutput::Map (dict) => dict.len(),
+        _ => 1
+    };
+
+    ExecuteOutput::Numeric(Numeric::Int(tally as i64))
+}
+
+// Arrange the values of the rhs into the shape given on the lhs, repeating them from the start
+// if there aren't enough, e.g. [2, 3] ⍴ ⍳ 4 gives [[0, 1, 2], [3, 0, 1]]
+pub fn execute_reshape(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    let shape: Vec<usize> = match lhs {
+        ExecuteOutput::Numeric (Numeric::Int(x)) if x >= 0 => vec![x as usize],
+        ExecuteOutput::Array (arr) => arr.into_iter().map(|x| match x {
+            ExecuteOutput::Numeric (Numeric::Int(x)) if x >= 0 => x as usize,
+            other => panic!("Cannot reshape with {:?}, shape must be non-negative ints", other)
+        }).collect(),
+        other => panic!("Cannot reshape with {:?}, shape must be a non-negative int or array of them", other)
+    };
+
+    let values = match rhs {
+        ExecuteOutput::Map (dict) => panic!("Cannot reshape map {:?}", dict),
+        other => {
+            let rank = array_shape(&other).len();
+            ravel(other, rank)
+        }
+    };
+
+    let size: usize = shape.iter().product();
+
+    if values.is_empty() && size > 0 {
+        panic!("Cannot reshape an empty array to shape {:?}", shape);
+    }
+
+    let values: Vec<ExecuteOutput> = values.into_iter().cycle().take(size).collect();
+
+    from_ravel(&shape, &values)
+}
+
+// Reverse the axes of an array, so rows become columns. Vectors and single values are unchanged
+pub fn execute_transpose(rhs: ExecuteOutput) -> ExecuteOutput {
+    let shape = array_shape(&rhs);
+
+    if let ExecuteOutput::Array (arr) = &rhs {
+        if shape.len() == 1 && arr.iter().any(|x| matches!(x, ExecuteOutput::Array(_))) {
+            panic!("RANK ERROR: cannot transpose ragged array {:?}", arr);
+        }
+    }
+
+    if shape.len() < 2 {
+        return rhs;
+    }
+
+    let values = ravel(rhs, shape.len());
+    let transposed_shape: Vec<usize> = shape.iter().rev().cloned().collect();
+
+    // Position of each output value in the ravel of the input, found by reading the output's
+    // index along each axis and walking the input with the axes reversed
+    let strides = row_major_strides(&shape);
+    let transposed: Vec<ExecuteOutput> = (0..values.len()).map(|position| {
+        let mut remaining = position;
+        let mut source = 0;
+
+        for (axis, length) in transposed_shape.iter().enumerate().rev() {
+            let index = remaining % length;
+            remaining /= length;
+            source += index * strides[shape.len() - 1 - axis];
+        }
+
+        values[source].clone()
+    }).collect();
+
+    from_ravel(&transposed_shape, &transposed)
+}
+
+// Shape of the rectangular part of an array, stopping at the first level where the items
+// aren't all arrays of the same shape
+pub fn array_shape(val: &ExecuteOutput) -> Vec<usize> {
+    let arr = match val {
+        ExecuteOutput::Array (arr) => arr,
+        _ => return vec![]
+    };
+
+    let mut shape = vec![arr.len()];
+
+    if let Some (first @ ExecuteOutput::Array (_)) = arr.first() {
+        let inner_shape = array_shape(first);
+
+        if arr.iter().all(|x| matches!(x, ExecuteOutput::Array(_)) && array_shape(x) == inner_shape) {
+            shape.extend(inner_shape);
+        }
+    }
+
+    shape
+}
+
+// Flatten the first rank axes of an array into a single list of values in row major order
+fn ravel(val: ExecuteOutput, rank: usize) -> Vec<ExecuteOutput> {
+    match (rank, val) {
+        (0, val) => vec![val],
+        (_, ExecuteOutput::Array (arr)) => arr.into_iter().flat_map(|x| ravel(x, rank - 1)).collect(),
+        (_, other) => vec![other]
+    }
+}
+
+// Build nested arrays of the given shape from values in row major order
+fn from_ravel(shape: &[usize], values: &[ExecuteOutput]) -> ExecuteOutput {
+    match shape.split_first() {
+        None => values[0].clone(),
+        Some ((length, inner_shape)) => {
+            let chunk_size: usize = inner_shape.iter().product();
+            let items = (0..*length).map(|i| from_ravel(inner_shape, &values[i * chunk_size..(i + 1) * chunk_size])).collect();
+
+            ExecuteOutput::Array(items)
+        }
+    }
+}
+
+fn row_major_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+
+    for axis in (0..shape.len().saturating_sub(1)).rev() {
+        strides[axis] = strides[axis + 1] * shape[axis + 1];
+    }
+
+    strides
+}
+
+fn shape_to_output(shape: &[usize]) -> ExecuteOutput {
+    ExecuteOutput::Array(shape.iter().map(|x| ExecuteOutput::Numeric(Numeric::Int(*x as i64))).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    #[test]
+    fn gives_shapes_as_far_as_arrays_are_rectangular() {
+        assert_eq!(evaluate("⍴ [[1, 2, 3], [4, 5, 6]]").to_string(), "[2, 3]");
+        assert_eq!(evaluate("⍴ [[1], [2, 3]]").to_string(), "[2]");
+        assert_eq!(evaluate("⍴ { 'a': 1, 'b': 2 }").to_string(), "[2]");
+        assert_eq!(evaluate("⍴ 5").to_string(), "[]");
+        assert_eq!(evaluate("≢ [[1, 2, 3], [4, 5, 6]]").to_string(), "2");
+    }
+
+    #[test]
+    fn reshapes_repeating_values() {
+        assert_eq!(evaluate("[2, 3] ⍴ ⍳ 4").to_string(), "[[0, 1, 2], [3, 0, 1]]");
+        assert_eq!(evaluate("4 ⍴ [[1, 2], [3, 4]]").to_string(), "[1, 2, 3, 4]");
+        assert_eq!(evaluate("[2, 2] ⍴ 7").to_string(), "[[7, 7], [7, 7]]");
+    }
+
+    #[test]
+    fn reshapes_ragged_arrays_by_their_items() {
+        assert_eq!(evaluate("[3] ⍴ [[1], [2, 3]]").to_string(), "[[1], [2, 3], [1]]");
+    }
+
+    #[test]
+    fn reshapes_empty_arrays_to_empty_shapes() {
+        assert_eq!(evaluate("[2, 0] ⍴ []").to_string(), "[[], []]");
+        assert_eq!(evaluate("0 ⍴ []").to_string(), "[]");
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot reshape an empty array to shape [2, 3]")]
+    fn rejects_reshaping_empty_arrays_to_non_empty_shapes() {
+        evaluate("[2, 3] ⍴ []");
+    }
+
+    #[test]
+    fn transposes_by_reversing_axes() {
+        assert_eq!(evaluate("⍉ [[1, 2, 3], [4, 5, 6]]").to_string(), "[[1, 4], [2, 5], [3, 6]]");
+        assert_eq!(evaluate("⍉ [[[1, 2], [3, 4]], [[5, 6], [7, 8]]]").to_string(), "[[[1, 5], [3, 7]], [[2, 6], [4, 8]]]");
+        assert_eq!(evaluate("⍉ ⍉ [[1, 2, 3], [4, 5, 6]]").to_string(), "[[1, 2, 3], [4, 5, 6]]");
+        assert_eq!(evaluate("⍉ [1, 2]").to_string(), "[1, 2]");
+        assert_eq!(evaluate("⍉ []").to_string(), "[]");
+    }
+
+    #[test]
+    fn transposes_the_rectangular_part_of_ragged_arrays() {
+        assert_eq!(evaluate("⍉ [[[1], [2, 3]], [[4], [5, 6]]]").to_string(), "[[[1], [4]], [[2, 3], [5, 6]]]");
+    }
+
+    #[test]
+    #[should_panic(expected = "RANK ERROR: cannot transpose ragged array")]
+    fn rejects_transposing_ragged_rows() {
+        evaluate("⍉ [[1], [2, 3]]");
+    }
+}
@@ -0,0 +1,49 @@
+use super::structures::ExecuteOutput;
+use std::panic::{self, AssertUnwindSafe};
+
+// Run a computation, giving the message of any runtime error it raises rather than stopping the
+// program. Runtime errors are panics, so this catches every panic, including any from a bug in
+// the interpreter itself. The panic hook is silenced meanwhile so caught errors aren't reported on stderr
+pub fn catch_error<F: FnOnce() -> ExecuteOutput>(compute: F) -> Result<ExecuteOutput, String> {
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    let result = panic::catch_unwind(AssertUnwindSafe(compute));
+
+    panic::set_hook(previous_hook);
+
+    result.map_err(|payload| match payload.downcast::<String>() {
+        Ok (message) => *message,
+        Err (payload) => match payload.downcast::<&str>() {
+            Ok (message) => message.to_string(),
+            Err (_) => "unknown error".to_string()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    #[test]
+    fn gives_the_value_when_there_is_no_error() {
+        assert_eq!(evaluate("(1 + 2) catch 0").to_string(), "3");
+    }
+
+    #[test]
+    fn gives_the_right_hand_side_on_an_error() {
+        assert_eq!(evaluate("(1 ÷ 0) catch 'none'").to_string(), "'none'");
+        assert_eq!(evaluate("(1 ÷ 0) catch ⎕error").to_string(), "'DOMAIN ERROR: cannot divide Int(1) by zero'");
+    }
+
+    #[test]
+    fn only_runs_the_right_hand_side_on_an_error() {
+        assert_eq!(evaluate("x <- 1\n(2 + 3) catch x <- 2\nx").to_string(), "1");
+    }
+
+    #[test]
+    #[should_panic(expected = "DOMAIN ERROR")]
+    fn raises_errors_from_the_right_hand_side() {
+        evaluate("(1 ÷ 0) catch 2 ÷ 0");
+    }
+}
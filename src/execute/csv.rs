@@ -0,0 +1,356 @@
+use super::structures::ExecuteOutput;
+use super::json::json_key;
+use super::file_io::{file_path, read_file, write_file};
+use crate::parse::structures::{Numeric, MapKey};
+use crate::parse::decimal::Decimal;
+use crate::parse::datetime::{Date, DateTime};
+use num_bigint::BigInt;
+use indexmap::IndexMap;
+
+// CSV files are read into arrays of records keyed by the header row. Each column is given the
+// narrowest type all its cells fit: int, then float, then string. Empty cells, and cells missing
+// from short rows, are null and don't count towards the type. Fields can be quoted with ", which
+// is escaped by doubling it, so quoted fields can hold delimiters and newlines.
+//
+// Options are given as a map on the left: 'delimiter' (a single character, ',' by default) and
+// 'types', a map of column names to 'int', 'float', 'decimal', 'string', 'date' or 'datetime'
+// overriding the inferred type. A string on the left is taken as the delimiter. As writecsv takes
+// the records on the left, its options go on the right along with the file's 'path'
+struct CsvOptions {
+    delimiter: char,
+    types: IndexMap<MapKey, String>
+}
+
+// Parse CSV text into an array of records
+pub fn execute_from_csv(lhs: Option<ExecuteOutput>, rhs: ExecuteOutput) -> ExecuteOutput {
+    match rhs {
+        ExecuteOutput::String (text) => parse_csv(&text, &csv_options(lhs)),
+        other => panic!("Cannot parse {:?} as CSV, must be a string", other)
+    }
+}
+
+// Read a CSV file, given its path, into an array of records
+pub fn execute_read_csv(lhs: Option<ExecuteOutput>, rhs: ExecuteOutput) -> ExecuteOutput {
+    parse_csv(&read_file(&file_path(rhs)), &csv_options(lhs))
+}
+
+// Write an array of records as CSV text. The header is every key in the order first seen, and
+// nulls and missing keys are written as empty cells
+pub fn execute_to_csv(lhs: Option<ExecuteOutput>, rhs: ExecuteOutput) -> ExecuteOutput {
+    ExecuteOutput::String(write_csv(rhs, csv_options(lhs).delimiter))
+}
+
+// Write the array of records on the lhs to the CSV file at the rhs path. To give options, the rhs
+// is a map of them holding the path under 'path', e.g. { 'path': 'out.csv', 'delimiter': ';' }
+pub fn execute_write_csv(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    let (path, options) = match rhs {
+        ExecuteOutput::Map (mut options) => match options.shift_remove(&MapKey::from("path")) {
+            Some (path) => (path, csv_options(Some(ExecuteOutput::Map(options)))),
+            None => panic!("CSV options for writecsv must give the file under 'path', found {:?}", options)
+        },
+        path => (path, csv_options(None))
+    };
+
+    write_file(&file_path(path), &write_csv(lhs, options.delimiter), false);
+
+    ExecuteOutput::Null
+}
+
+fn csv_options(lhs: Option<ExecuteOutput>) -> CsvOptions {
+    let mut options = CsvOptions { delimiter: ',', types: IndexMap::new() };
+
+    match lhs {
+        None => {},
+        Some (ExecuteOutput::String (delimiter)) => options.delimiter = csv_delimiter(&delimiter),
+        Some (ExecuteOutput::Map (dict)) => {
+            for (key, value) in dict {
+                match (key, value) {
+                    (MapKey::String (key), ExecuteOutput::String (delimiter)) if key == "delimiter" => options.delimiter = csv_delimiter(&delimiter),
+                    (MapKey::String (key), ExecuteOutput::Map (types)) if key == "types" => {
+                        for (column, type_name) in types {
+                            match type_name {
+                                ExecuteOutput::String (type_name) => options.types.insert(column, type_name),
+                                other => panic!("CSV column type must be a type name, found {:?}", other)
+                            };
+                        }
+                    },
+                    (key, value) => panic!("Unknown CSV option {:?}: {:?}", key, value)
+                }
+            }
+        },
+        Some (other) => panic!("CSV options must be a delimiter or map of options, found {:?}", other)
+    };
+
+    options
+}
+
+fn csv_delimiter(delimiter: &str) -> char {
+    let mut chars = delimiter.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some (c), None) if c != '"' && c != '\n' && c != '\r' => c,
+        _ => panic!("CSV delimiter must be a single character, found {:?}", delimiter)
+    }
+}
+
+fn parse_csv(text: &str, options: &CsvOptions) -> ExecuteOutput {
+    let mut rows = split_rows(text, options.delimiter).into_iter();
+
+    let header: Vec<MapKey> = match rows.next() {
+        Some (header) => header.into_iter().map(|cell| MapKey::String(cell.unwrap_or_default())).collect(),
+        None => return ExecuteOutput::Array(vec![])
+    };
+    let rows: Vec<Vec<Option<String>>> = rows.collect();
+
+    let types: Vec<String> = header.iter().enumerate().map(|(i, column)| match options.types.get(column) {
+        Some (type_name) => type_name.clone(),
+        None => infer_column_type(rows.iter().filter_map(|row| row.get(i).and_then(|cell| cell.as_deref())))
+    }).collect();
+
+    let records = rows.into_iter().map(|row| {
+        let mut record: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+        let mut cells = row.into_iter();
+
+        for (column, type_name) in header.iter().zip(&types) {
+            let value = match cells.next().flatten() {
+                Some (cell) => parse_cell(cell, type_name),
+                None => ExecuteOutput::Null
+            };
+
+            record.insert(column.clone(), value);
+        }
+
+        ExecuteOutput::Map(record)
+    }).collect();
+
+    ExecuteOutput::Array(records)
+}
+
+// Split CSV text into rows of cells, where None is an empty unquoted cell. Blank lines are skipped
+fn split_rows(text: &str, delimiter: char) -> Vec<Vec<Option<String>>> {
+    let mut rows: Vec<Vec<Option<String>>> = Vec::new();
+    let mut row: Vec<Option<String>> = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    let end_cell = |row: &mut Vec<Option<String>>, cell: &mut String, quoted: &mut bool| {
+        let value = std::mem::take(cell);
+        row.push(if value.is_empty() && !*quoted { None } else { Some(value) });
+        *quoted = false;
+    };
+
+    while let Some (c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            },
+            '"' if in_quotes => in_quotes = false,
+            '"' if cell.is_empty() && !quoted => {
+                in_quotes = true;
+                quoted = true;
+            },
+            c if in_quotes => cell.push(c),
+            c if c == delimiter => end_cell(&mut row, &mut cell, &mut quoted),
+            '\r' if chars.peek() == Some(&'\n') => {},
+            '\n' | '\r' => {
+                if !row.is_empty() || !cell.is_empty() || quoted {
+                    end_cell(&mut row, &mut cell, &mut quoted);
+                    rows.push(std::mem::take(&mut row));
+                }
+            },
+            c => cell.push(c)
+        }
+    }
+
+    if in_quotes {
+        panic!("Invalid CSV: unterminated quoted field {:?}", cell);
+    }
+
+    if !row.is_empty() || !cell.is_empty() || quoted {
+        end_cell(&mut row, &mut cell, &mut quoted);
+        rows.push(row);
+    }
+
+    rows
+}
+
+// The narrowest type every cell fits. A column with no cells, i.e. all null, is int, though as
+// all its values are null the type makes no difference
+fn infer_column_type<'a>(cells: impl Iterator<Item = &'a str>) -> String {
+    let mut column_type = "int";
+
+    for cell in cells {
+        column_type = match column_type {
+            "int" if is_int(cell) => "int",
+            "int" | "float" if is_float(cell) => "float",
+            _ => return "string".to_string()
+        };
+    }
+
+    column_type.to_string()
+}
+
+// A sign is allowed on either kind of number, so +5 is an int just as -5 is
+fn is_int(cell: &str) -> bool {
+    let digits = cell.strip_prefix(['-', '+']).unwrap_or(cell);
+
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+// Only plain decimal and exponent forms count, so words like inf and nan stay strings
+fn is_float(cell: &str) -> bool {
+    cell.chars().any(|c| c.is_ascii_digit())
+        && cell.chars().all(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        && cell.parse::<f64>().is_ok()
+}
+
+// Read a single piece of text as an int or float if it looks like one, otherwise a string
+pub fn infer_value(text: String) -> ExecuteOutput {
+    let type_name = infer_column_type(std::iter::once(text.as_str()));
+
+    parse_cell(text, &type_name)
+}
+
+fn parse_cell(cell: String, type_name: &str) -> ExecuteOutput {
+    match type_name {
+        "string" => ExecuteOutput::String(cell),
+        "int" if is_int(&cell) => ExecuteOutput::Numeric(match cell.parse::<i64>() {
+            Ok (integer) => Numeric::Int(integer),
+            Err (_) => Numeric::BigInt(cell.parse::<BigInt>().unwrap())
+        }),
+        "float" if is_float(&cell) => ExecuteOutput::Numeric(Numeric::Float(cell.parse().unwrap())),
+        "decimal" if is_float(&cell) && !cell.contains(['e', 'E']) => ExecuteOutput::Numeric(Numeric::Decimal(Decimal::parse(&cell))),
+        "date" => ExecuteOutput::Date(Date::parse(&cell)),
+        "datetime" => ExecuteOutput::DateTime(DateTime::parse(&cell)),
+        "int" | "float" | "decimal" => panic!("Cannot read CSV cell {:?} as {}", cell, type_name),
+        unknown => panic!("Unknown CSV column type {:?}", unknown)
+    }
+}
+
+fn write_csv(val: ExecuteOutput, delimiter: char) -> String {
+    let records: Vec<IndexMap<MapKey, ExecuteOutput>> = match val {
+        ExecuteOutput::Array (arr) => arr.into_iter().map(|x| match x {
+            ExecuteOutput::Map (dict) => dict,
+            other => panic!("Cannot write {:?} as a CSV row, must be a map", other)
+        }).collect(),
+        ExecuteOutput::Map (dict) => vec![dict],
+        other => panic!("Cannot write {:?} as CSV, must be an array of maps", other)
+    };
+
+    let mut header: Vec<&MapKey> = Vec::new();
+
+    for record in &records {
+        for key in record.keys() {
+            if !header.contains(&key) {
+                header.push(key);
+            }
+        }
+    }
+
+    let separator = delimiter.to_string();
+    let mut lines: Vec<String> = vec![header.iter().map(|key| csv_field(&json_key(key), delimiter)).collect::<Vec<String>>().join(&separator)];
+
+    for record in &records {
+        let cells: Vec<String> = header.iter().map(|key| match record.get(*key) {
+            None | Some (ExecuteOutput::Null) => String::new(),
+            Some (value) => csv_field(&csv_cell(value), delimiter)
+        }).collect();
+
+        lines.push(cells.join(&separator));
+    }
+
+    lines.join("\n") + "\n"
+}
+
+fn csv_cell(val: &ExecuteOutput) -> String {
+    match val {
+        ExecuteOutput::String (text) => text.clone(),
+        ExecuteOutput::Numeric (Numeric::Int(x)) => x.to_string(),
+        ExecuteOutput::Numeric (Numeric::BigInt(x)) => x.to_string(),
+        // Debug formatting keeps the fraction of whole floats, so they read back as floats
+        ExecuteOutput::Numeric (Numeric::Float(x)) => format!("{:?}", x),
+        ExecuteOutput::Numeric (Numeric::Decimal(x)) => format!("{:?}", x),
+        ExecuteOutput::Date (x) => format!("{:?}", x),
+        ExecuteOutput::DateTime (x) => format!("{:?}", x),
+        ExecuteOutput::Duration (x) => format!("{:?}", x),
+        other => panic!("Cannot write {:?} to a CSV cell", other)
+    }
+}
+
+// Quote fields holding the delimiter, quotes or line breaks, and empty strings so they read
+// back as strings rather than nulls
+fn csv_field(text: &str, delimiter: char) -> String {
+    match text.is_empty() || text.contains([delimiter, '"', '\n', '\r']) {
+        true => format!("\"{}\"", text.replace('"', "\"\"")),
+        false => text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execute::test_helpers::evaluate;
+
+    fn from_csv(text: &str) -> String {
+        execute_from_csv(None, ExecuteOutput::String(text.to_string())).to_string()
+    }
+
+    #[test]
+    fn infers_column_types() {
+        assert_eq!(from_csv("id,price,name\n1,2,a\n-3,4.5,7\n"), "[{'id': 1, 'price': 2.0, 'name': 'a'}, {'id': -3, 'price': 4.5, 'name': '7'}]");
+        assert_eq!(from_csv("a,b\n,inf\n,nan\n"), "[{'a': null, 'b': 'inf'}, {'a': null, 'b': 'nan'}]");
+        assert_eq!(from_csv("a,b,c\n+5,-5,+5.5\n"), "[{'a': 5, 'b': -5, 'c': 5.5}]");
+    }
+
+    #[test]
+    fn reads_quoted_fields_and_short_rows() {
+        assert_eq!(from_csv("a,b,c\r\n\"x,y\",\"say \"\"hi\"\"\",\"\"\r\n\"two\nlines\"\r\n"), "[{'a': 'x,y', 'b': 'say \"hi\"', 'c': ''}, {'a': 'two\\nlines', 'b': null, 'c': null}]");
+    }
+
+    #[test]
+    fn overrides_column_types() {
+        let options = evaluate("{ 'delimiter': ';', 'types': { 'id': 'string', 'total': 'decimal', 'placed': 'date' } }");
+        let records = execute_from_csv(Some(options), ExecuteOutput::String("id;total;placed\n007;12.50;2021-03-04\n".to_string()));
+
+        assert_eq!(records.to_string(), "[{'id': '007', 'total': 12.50d, 'placed': @2021-03-04}]");
+    }
+
+    #[test]
+    fn writes_and_reads_back() {
+        let records = evaluate("[{ 'a': 'x,y', 'b': 1.0 }, { 'a': '', 'c': 'say \"hi\"' }, { 'b': null }]");
+        let text = write_csv(records.clone(), ',');
+
+        assert_eq!(text, "a,b,c\n\"x,y\",1.0,\n\"\",,\"say \"\"hi\"\"\"\n,,\n");
+        assert_eq!(from_csv(&text), "[{'a': 'x,y', 'b': 1.0, 'c': null}, {'a': '', 'b': null, 'c': 'say \"hi\"'}, {'a': null, 'b': null, 'c': null}]");
+    }
+
+    #[test]
+    fn writes_files_with_a_delimiter() {
+        let path = std::env::temp_dir().join(format!("maplang_writecsv_{}.csv", std::process::id()));
+        let path_value = ExecuteOutput::String(path.display().to_string());
+
+        let options = format!("{{ 'path': {}, 'delimiter': ';' }}", path_value);
+        execute_write_csv(evaluate("[{ 'a': 1, 'b': 'x;y' }]"), evaluate(&options));
+        let text = read_file(&path.display().to_string());
+        let records = execute_read_csv(Some(ExecuteOutput::String(";".to_string())), path_value);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(text, "a;b\n1;\"x;y\"\n");
+        assert_eq!(records.to_string(), "[{'a': 1, 'b': 'x;y'}]");
+    }
+
+    #[test]
+    fn writes_records_holding_any_keys() {
+        let path = std::env::temp_dir().join(format!("maplang_writecsv_records_{}.csv", std::process::id()));
+        let path_value = ExecuteOutput::String(path.display().to_string());
+
+        execute_write_csv(evaluate("{ 'records': 3, 'path': 'x' }"), path_value);
+        let text = read_file(&path.display().to_string());
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(text, "records,path\n3,x\n");
+    }
+}
@@ -0,0 +1,82 @@
+use super::structures::ExecuteOutput;
+use super::system_variables::get_system_variable;
+use super::operator_reduce::{initial_reduce_value, IdentityType};
+use crate::parse::structures::{DyadicVerb, MapKey};
+use indexmap::IndexMap;
+
+// Line up the values of two maps by key, ready for a pervasive dyadic verb to be applied to each pair.
+// Keys are unioned: a key missing from one side is filled with ⎕fill if it has been set, otherwise
+// with the identity of the verb shaped like the value on the other side, e.g. 0 for + and 1 for ×.
+// - and ÷ only have an identity on the right, so a key missing from the left is null, as are keys of
+// verbs without an identity and values such as strings that have none
+pub fn align_dicts(verb: DyadicVerb, lhs_map: IndexMap<MapKey, ExecuteOutput>, mut rhs_map: IndexMap<MapKey, ExecuteOutput>) -> Vec<(MapKey, ExecuteOutput, ExecuteOutput)> {
+    let mut aligned: Vec<(MapKey, ExecuteOutput, ExecuteOutput)> = Vec::new();
+
+    for (key, lhs_value) in lhs_map {
+        let rhs_value = match rhs_map.shift_remove(&key) {
+            Some (rhs_value) => rhs_value,
+            None => missing_value(&verb, &lhs_value, false)
+        };
+
+        aligned.push((key, lhs_value, rhs_value));
+    }
+
+    for (key, rhs_value) in rhs_map {
+        let lhs_value = missing_value(&verb, &rhs_value, true);
+
+        aligned.push((key, lhs_value, rhs_value));
+    }
+
+    aligned
+}
+
+fn missing_value(verb: &DyadicVerb, template: &ExecuteOutput, missing_lhs: bool) -> ExecuteOutput {
+    match get_system_variable("⎕fill") {
+        ExecuteOutput::Null => {},
+        fill => return fill
+    };
+
+    let identity_type = match (verb, missing_lhs) {
+        (DyadicVerb::Add, _) | (DyadicVerb::Subtract, false) => IdentityType::Add,
+        (DyadicVerb::Multiply, _) | (DyadicVerb::Divide, false) => IdentityType::Multiply,
+        _ => return ExecuteOutput::Null
+    };
+
+    identity_like(template, identity_type)
+}
+
+// The identity shaped like the template, with null in place of values that have no identity
+fn identity_like(template: &ExecuteOutput, identity_type: IdentityType) -> ExecuteOutput {
+    match template {
+        ExecuteOutput::Numeric (_) | ExecuteOutput::Duration (_) | ExecuteOutput::Null => initial_reduce_value(template.clone(), identity_type),
+        ExecuteOutput::Array (arr) => ExecuteOutput::Array(arr.iter().map(|x| identity_like(x, identity_type)).collect()),
+        ExecuteOutput::Map (dict) => ExecuteOutput::Map(dict.iter().map(|(key, x)| (key.clone(), identity_like(x, identity_type))).collect()),
+        _ => ExecuteOutput::Null
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    #[test]
+    fn fills_missing_keys_with_the_identity() {
+        assert_eq!(evaluate("{ 'a': 1, 'b': 2 } + { 'a': 10 }").to_string(), "{'a': 11, 'b': 2}");
+        assert_eq!(evaluate("{ 'a': 6, 'b': 2 } ÷ { 'a': 3 }").to_string(), "{'a': 2, 'b': 2}");
+        assert_eq!(evaluate("{ 'a': 6 } - { 'a': 1, 'b': 2 }").to_string(), "{'a': 5, 'b': null}");
+        assert_eq!(evaluate("{ 'a': 6 } ÷ { 'a': 3, 'b': 2 }").to_string(), "{'a': 2, 'b': null}");
+    }
+
+    #[test]
+    fn fills_missing_keys_with_the_fill_value() {
+        assert_eq!(evaluate("⎕fill <- 1000\n{ 'salary': 300, 'bonus': 20 } × { 'salary': 2 }").to_string(), "{'salary': 600, 'bonus': 20000}");
+        assert_eq!(evaluate("⎕fill <- 0\n{ 'a': 6 } - { 'a': 1, 'b': 2 }").to_string(), "{'a': 5, 'b': -2}");
+        assert_eq!(evaluate("⎕fill <- 1\n{ 'a': 1 } = { 'b': 1 }").to_string(), "{'a': 1, 'b': 1}");
+    }
+
+    #[test]
+    fn fills_values_without_an_identity_with_null() {
+        assert_eq!(evaluate("{ 'a': 1 } + { 'a': 1, 'name': 'x' }").to_string(), "{'a': 2, 'name': null}");
+        assert_eq!(evaluate("{ 'a': 1, 'tags': { 'n': 2, 'name': 'x' } } × { 'a': 3 }").to_string(), "{'a': 3, 'tags': {'n': 2, 'name': null}}");
+    }
+}
@@ -0,0 +1,328 @@
+use super::structures::ExecuteOutput;
+use super::system_variables::get_system_variable;
+use super::array_shape::array_shape;
+use crate::parse::structures::{Numeric, MapKey};
+use indexmap::IndexMap;
+
+// Values are printed in a layout picked by their shape:
+// - arrays of maps as a table, with a column per key
+// - matrices as aligned rows, and simple arrays as their values separated by spaces
+// - other nested arrays boxed APL style, each item drawn in its own box
+// - maps as a key and value per line
+// Tables, matrices and maps show at most ⎕rows rows (20 by default), and lines are cut off at
+// ⎕width characters, which defaults to the COLUMNS environment variable or 80
+const DEFAULT_ROWS: usize = 20;
+const DEFAULT_WIDTH: usize = 80;
+// Longest value shown in a table cell before it is cut off
+const MAX_CELL_WIDTH: usize = 40;
+
+pub fn display(val: &ExecuteOutput) -> String {
+    // Setting ⎕print <- 'literal' prints values as MapLang source, to paste back into scripts
+    match get_system_variable("⎕print") {
+        ExecuteOutput::Null => {},
+        ExecuteOutput::String (style) if style == "layout" => {},
+        ExecuteOutput::String (style) if style == "literal" => return val.to_string(),
+        other => panic!("⎕print must be 'layout' or 'literal', found {:?}", other)
+    };
+
+    let width = display_width();
+
+    // Text printed on its own keeps its line breaks, while inside a layout they are escaped
+    if let ExecuteOutput::String (text) = val {
+        return text.lines().map(|line| truncate(line, width)).collect::<Vec<String>>().join("\n");
+    }
+
+    render(val).into_iter().map(|line| truncate(&line, width)).collect::<Vec<String>>().join("\n")
+}
+
+fn render(val: &ExecuteOutput) -> Vec<String> {
+    match val {
+        ExecuteOutput::Array (arr) if arr.is_empty() => vec!["[]".to_string()],
+        ExecuteOutput::Array (arr) if arr.iter().all(|x| matches!(x, ExecuteOutput::Map(_))) => render_table(arr),
+        ExecuteOutput::Array (arr) if array_shape(val).len() == 2 && arr.iter().all(is_simple_array) => render_matrix(arr),
+        ExecuteOutput::Array (arr) if arr.iter().all(is_scalar) => vec![render_inline(val)],
+        ExecuteOutput::Array (arr) => render_boxed(arr),
+        ExecuteOutput::Map (dict) => render_map(dict),
+        scalar => vec![format_scalar(scalar)]
+    }
+}
+
+fn render_table(records: &[ExecuteOutput]) -> Vec<String> {
+    let mut columns: Vec<&MapKey> = Vec::new();
+
+    for record in records {
+        if let ExecuteOutput::Map (dict) = record {
+            for key in dict.keys() {
+                if !columns.contains(&key) {
+                    columns.push(key);
+                }
+            }
+        }
+    }
+
+    let (shown, hidden) = limit_rows(records);
+
+    let rows: Vec<Vec<Option<&ExecuteOutput>>> = shown.iter().map(|record| match record {
+        ExecuteOutput::Map (dict) => columns.iter().map(|key| dict.get(*key)).collect(),
+        _ => vec![]
+    }).collect();
+
+    let header: Vec<String> = columns.iter().map(|key| format_key(key)).collect();
+    let cells: Vec<Vec<String>> = rows.iter().map(|row| row.iter().map(|cell| match cell {
+        Some (value) => truncate(&render_inline(value), MAX_CELL_WIDTH),
+        None => String::new()
+    }).collect()).collect();
+
+    // Columns holding only numbers are right aligned
+    let numeric: Vec<bool> = (0..columns.len()).map(|i| {
+        rows.iter().all(|row| matches!(row[i], None | Some(ExecuteOutput::Numeric(_)) | Some(ExecuteOutput::Null)))
+    }).collect();
+    let widths: Vec<usize> = (0..columns.len()).map(|i| {
+        cells.iter().map(|row| text_width(&row[i])).chain(std::iter::once(text_width(&header[i]))).max().unwrap_or(0)
+    }).collect();
+
+    let mut lines = vec![table_row(&header, &widths, &numeric)];
+    lines.push(widths.iter().map(|width| "─".repeat(*width)).collect::<Vec<String>>().join("─┼─"));
+    lines.extend(cells.iter().map(|row| table_row(row, &widths, &numeric)));
+
+    if hidden > 0 {
+        lines.push(format!("… {} more rows", hidden));
+    }
+
+    lines
+}
+
+fn table_row(cells: &[String], widths: &[usize], numeric: &[bool]) -> String {
+    let padded: Vec<String> = cells.iter().zip(widths).zip(numeric).map(|((cell, width), numeric)| pad(cell, *width, *numeric)).collect();
+
+    padded.join(" │ ").trim_end().to_string()
+}
+
+fn render_matrix(rows: &[ExecuteOutput]) -> Vec<String> {
+    let (shown, hidden) = limit_rows(rows);
+
+    let cells: Vec<Vec<(String, bool)>> = shown.iter().map(|row| match row {
+        ExecuteOutput::Array (items) => items.iter().map(|x| (format_scalar(x), matches!(x, ExecuteOutput::Numeric(_)))).collect(),
+        _ => vec![]
+    }).collect();
+
+    let column_count = cells.first().map(|row| row.len()).unwrap_or(0);
+    let widths: Vec<usize> = (0..column_count).map(|i| cells.iter().map(|row| text_width(&row[i].0)).max().unwrap_or(0)).collect();
+
+    let mut lines: Vec<String> = cells.iter().map(|row| {
+        row.iter().zip(&widths).map(|((cell, numeric), width)| pad(cell, *width, *numeric)).collect::<Vec<String>>().join(" ").trim_end().to_string()
+    }).collect();
+
+    if hidden > 0 {
+        lines.push(format!("… {} more rows", hidden));
+    }
+
+    lines
+}
+
+// Draw each item in a box, side by side, e.g.
+// ┌───┬─────┐
+// │1 2│3 4 5│
+// └───┴─────┘
+fn render_boxed(items: &[ExecuteOutput]) -> Vec<String> {
+    let rendered: Vec<Vec<String>> = items.iter().map(render).collect();
+    let widths: Vec<usize> = rendered.iter().map(|lines| lines.iter().map(|line| text_width(line)).max().unwrap_or(0)).collect();
+    let height = rendered.iter().map(|lines| lines.len()).max().unwrap_or(0);
+
+    let border = |left: &str, middle: &str, right: &str| {
+        format!("{}{}{}", left, widths.iter().map(|width| "─".repeat(*width)).collect::<Vec<String>>().join(middle), right)
+    };
+
+    let mut lines = vec![border("┌", "┬", "┐")];
+
+    for i in 0..height {
+        let row: Vec<String> = rendered.iter().zip(&widths).map(|(item, width)| pad(item.get(i).map(String::as_str).unwrap_or(""), *width, false)).collect();
+        lines.push(format!("│{}│", row.join("│")));
+    }
+
+    lines.push(border("└", "┴", "┘"));
+
+    lines
+}
+
+// Each key padded to the same width, followed by its value, e.g.
+// salary │ 1300
+// age    │ 23.3
+fn render_map(dict: &IndexMap<MapKey, ExecuteOutput>) -> Vec<String> {
+    if dict.is_empty() {
+        return vec!["{}".to_string()];
+    }
+
+    let entries: Vec<(&MapKey, &ExecuteOutput)> = dict.iter().collect();
+    let (shown, hidden) = limit_rows(&entries);
+
+    let keys: Vec<String> = shown.iter().map(|(key, _)| format_key(key)).collect();
+    let key_width = keys.iter().map(|key| text_width(key)).max().unwrap_or(0);
+
+    let mut lines: Vec<String> = Vec::new();
+
+    for (key, (_, value)) in keys.iter().zip(shown) {
+        for (i, line) in render(value).into_iter().enumerate() {
+            let key = if i == 0 { key.as_str() } else { "" };
+            lines.push(format!("{} │ {}", pad(key, key_width, false), line));
+        }
+    }
+
+    if hidden > 0 {
+        lines.push(format!("… {} more keys", hidden));
+    }
+
+    lines
+}
+
+// A single line form of a value for table cells and simple arrays
+fn render_inline(val: &ExecuteOutput) -> String {
+    match val {
+        ExecuteOutput::Array (arr) => {
+            let items: Vec<String> = arr.iter().map(|x| match x {
+                ExecuteOutput::Array (_) => format!("[{}]", render_inline(x)),
+                other => render_inline(other)
+            }).collect();
+
+            items.join(" ")
+        },
+        ExecuteOutput::Map (dict) => {
+            let entries: Vec<String> = dict.iter().map(|(key, value)| format!("{}: {}", format_key(key), render_inline(value))).collect();
+
+            format!("{{{}}}", entries.join(", "))
+        },
+        scalar => format_scalar(scalar)
+    }
+}
+
+fn format_scalar(val: &ExecuteOutput) -> String {
+    match val {
+        ExecuteOutput::Numeric (Numeric::Int(x)) => x.to_string(),
+        ExecuteOutput::Numeric (Numeric::BigInt(x)) => x.to_string(),
+        ExecuteOutput::Numeric (Numeric::Float(x)) => x.to_string(),
+        ExecuteOutput::Numeric (Numeric::Decimal(x)) => format!("{:?}", x),
+        // Line breaks are escaped so a string stays on one line of its layout
+        ExecuteOutput::String (text) => text.replace('\r', "\\r").replace('\n', "\\n"),
+        ExecuteOutput::Date (x) => format!("{:?}", x),
+        ExecuteOutput::DateTime (x) => format!("{:?}", x),
+        ExecuteOutput::Duration (x) => format!("{:?}", x),
+        ExecuteOutput::Function (x) => x.source.clone(),
+        ExecuteOutput::Null => "null".to_string(),
+        nested => render_inline(nested)
+    }
+}
+
+fn format_key(key: &MapKey) -> String {
+    match key {
+        MapKey::String (key) => key.clone(),
+        MapKey::Int (key) => key.to_string(),
+        MapKey::Date (key) => format!("{:?}", key),
+        MapKey::Composite (keys) => format!("[{}]", keys.iter().map(format_key).collect::<Vec<String>>().join(" "))
+    }
+}
+
+fn is_scalar(val: &ExecuteOutput) -> bool {
+    !matches!(val, ExecuteOutput::Array(_) | ExecuteOutput::Map(_))
+}
+
+fn is_simple_array(val: &ExecuteOutput) -> bool {
+    matches!(val, ExecuteOutput::Array(arr) if arr.iter().all(is_scalar))
+}
+
+// Split off the rows that fit within ⎕rows, giving the number left over
+fn limit_rows<T>(rows: &[T]) -> (&[T], usize) {
+    let max_rows = match get_system_variable("⎕rows") {
+        ExecuteOutput::Null => DEFAULT_ROWS,
+        ExecuteOutput::Numeric (Numeric::Int(x)) if x > 0 => x as usize,
+        other => panic!("⎕rows must be a positive int, found {:?}", other)
+    };
+
+    match rows.len() > max_rows {
+        true => (&rows[..max_rows], rows.len() - max_rows),
+        false => (rows, 0)
+    }
+}
+
+fn display_width() -> usize {
+    match get_system_variable("⎕width") {
+        ExecuteOutput::Null => std::env::var("COLUMNS").ok().and_then(|columns| columns.parse().ok()).unwrap_or(DEFAULT_WIDTH),
+        ExecuteOutput::Numeric (Numeric::Int(x)) if x > 0 => x as usize,
+        other => panic!("⎕width must be a positive int, found {:?}", other)
+    }
+}
+
+fn text_width(text: &str) -> usize {
+    text.chars().count()
+}
+
+fn pad(text: &str, width: usize, right_align: bool) -> String {
+    let padding = " ".repeat(width.saturating_sub(text_width(text)));
+
+    match right_align {
+        true => format!("{}{}", padding, text),
+        false => format!("{}{}", text, padding)
+    }
+}
+
+// Cut text down to the given width, marking where it was cut with …
+fn truncate(text: &str, width: usize) -> String {
+    match text_width(text) > width {
+        true => format!("{}…", text.chars().take(width.saturating_sub(1)).collect::<String>()),
+        false => text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::display;
+    use crate::execute::test_helpers::evaluate;
+
+    fn show(source: &str) -> String {
+        display(&evaluate(&format!("⎕width <- 80\n{}", source)))
+    }
+
+    #[test]
+    fn lays_out_records_as_a_table() {
+        assert_eq!(
+            show("[{ 'name': 'Ada', 'age': 36 }, { 'name': 'Alan', 'salary': 1300.5 }]"),
+            "name │ age │ salary\n─────┼─────┼───────\nAda  │  36 │\nAlan │     │ 1300.5"
+        );
+    }
+
+    #[test]
+    fn right_aligns_numeric_columns_of_a_matrix() {
+        assert_eq!(show("[[1, 20, 3], [400, 5, 6]]"), "  1 20 3\n400  5 6");
+    }
+
+    #[test]
+    fn boxes_ragged_and_nested_arrays() {
+        assert_eq!(show("[[1, 2], [3, 4, 5]]"), "┌───┬─────┐\n│1 2│3 4 5│\n└───┴─────┘");
+        assert_eq!(show("[1, [2, 3]]"), "┌─┬───┐\n│1│2 3│\n└─┴───┘");
+    }
+
+    #[test]
+    fn lays_out_maps_as_keys_and_values() {
+        assert_eq!(show("{ 'salary': 1300, 'age': 23.5, 'tags': ['a', 'b'] }"), "salary │ 1300\nage    │ 23.5\ntags   │ a b");
+    }
+
+    #[test]
+    fn escapes_line_breaks_inside_a_layout() {
+        assert_eq!(show("{ 'note': 'two\\nlines', 'n': 1 }"), "note │ two\\nlines\nn    │ 1");
+        assert_eq!(show("[['a\\nb', 'c']]"), "a\\nb c");
+        assert_eq!(show("[{ 'a': 'x\\ny' }]"), "a\n────\nx\\ny");
+        assert_eq!(show("'one\\ntwo'"), "one\ntwo");
+    }
+
+    #[test]
+    fn truncates_to_rows() {
+        assert_eq!(show("⎕rows <- 2\n[{ 'a': 1 }, { 'a': 2 }, { 'a': 3 }, { 'a': 4 }]"), "a\n─\n1\n2\n… 2 more rows");
+        assert_eq!(show("⎕rows <- 2\n[[1, 2], [3, 4], [5, 6]]"), "1 2\n3 4\n… 1 more rows");
+        assert_eq!(show("⎕rows <- 2\n{ 'a': 1, 'b': 2, 'c': 3 }"), "a │ 1\nb │ 2\n… 1 more keys");
+    }
+
+    #[test]
+    fn cuts_lines_off_at_the_width() {
+        assert_eq!(show("⎕width <- 10\n⍳ 20"), "0 1 2 3 4…");
+        assert_eq!(show("⎕width <- 10\n{ 'k': 'abcdefghijklmnop' }"), "k │ abcde…");
+    }
+}
@@ -1,13 +1,18 @@
 use super::structures::ExecuteOutput;
-use crate::parse::structures::Numeric;
-use std::collections::HashMap;
+use crate::parse::structures::{Numeric, MapKey};
+use super::system_variables::get_system_variable;
+use super::map_key::to_map_key;
+use indexmap::IndexMap;
 
 pub fn execute_access(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
     match (lhs, rhs) {
+        // Accessing into a null gives null, so chains of accesses over sparse data don't fail
+        (ExecuteOutput::Null, _) => ExecuteOutput::Null,
         (ExecuteOutput::Array (lhs_array), ExecuteOutput::Array (rhs_array)) => execute_access_array_with_array(lhs_array, rhs_array),
-        (ExecuteOutput::Array (lhs_array), ExecuteOutput::String (rhs_string)) => execute_access_array_with_string(lhs_array, rhs_string),
+        (ExecuteOutput::Array (lhs_array), ExecuteOutput::String (rhs_string)) => execute_access_array_with_key(lhs_array, MapKey::String(rhs_string)),
         (ExecuteOutput::Array (lhs_array), ExecuteOutput::Numeric (rhs_numeric)) => execute_access_array_with_numeric(lhs_array, rhs_numeric),
-        (ExecuteOutput::Map (lhs_map), ExecuteOutput::String (rhs_string)) => execute_access_dict_with_string(lhs_map, rhs_string),
+        // Maps can be keyed by strings, ints, dates or arrays of these as composite keys
+        (ExecuteOutput::Map (lhs_map), rhs_key) => execute_access_dict_with_key(lhs_map, to_map_key(rhs_key)),
         (lhs_other, rhs_other) => panic!("Cannot use access with {:?} . {:?}", lhs_other, rhs_other)
     }
 }
@@ -25,14 +30,17 @@ fn execute_access_array_with_array(lhs_array: Vec<ExecuteOutput>, rhs_array: Vec
     let mut output: Vec<ExecuteOutput> = Vec::new();
 
     for index in indicies {
-        output.push(lhs_array.get(index).unwrap().clone())
+        output.push(match lhs_array.get(index) {
+            Some (val) => val.clone(),
+            None => missing_access(format!("index {} of array of length {}", index, lhs_array.len()))
+        })
     }
 
     ExecuteOutput::Array(output)
 }
 
-fn execute_access_array_with_string(lhs_array: Vec<ExecuteOutput>, rhs_string: String) -> ExecuteOutput {
-    let mut dicts: Vec<HashMap<String, ExecuteOutput>> = Vec::new();
+pub fn execute_access_array_with_key(lhs_array: Vec<ExecuteOutput>, rhs_key: MapKey) -> ExecuteOutput {
+    let mut dicts: Vec<IndexMap<MapKey, ExecuteOutput>> = Vec::new();
 
     for val in lhs_array {
         match val {
@@ -43,11 +51,8 @@ fn execute_access_array_with_string(lhs_array: Vec<ExecuteOutput>, rhs_string: S
 
     let mut output: Vec<ExecuteOutput> = Vec::new();
 
-    // wrap up string and dicts into ExecuteOutput in order to pass back into execute_access
-    let rhs_string = ExecuteOutput::String(rhs_string);
     for dict in dicts {
-        let dict = ExecuteOutput::Map(dict);
-        output.push(execute_access(dict, rhs_string.clone()));
+        output.push(execute_access_dict_with_key(dict, rhs_key.clone()));
     }
 
     ExecuteOutput::Array(output)
@@ -59,10 +64,43 @@ fn execute_access_array_with_numeric(lhs_array: Vec<ExecuteOutput>, rhs_numeric:
         other => panic!("Cannot access array via non int {:?} numeric", other)
     };
 
-    lhs_array.get(rhs_numeric).unwrap().clone()
+    match lhs_array.get(rhs_numeric) {
+        Some (val) => val.clone(),
+        None => missing_access(format!("index {} of array of length {}", rhs_numeric, lhs_array.len()))
+    }
+}
+
+fn execute_access_dict_with_key(lhs_map: IndexMap<MapKey, ExecuteOutput>, rhs_key: MapKey) -> ExecuteOutput {
+    match lhs_map.get(&rhs_key) {
+        Some (val) => val.clone(),
+        None => missing_access(format!("key {:?} of map with keys {:?}", rhs_key, lhs_map.keys()))
+    }
+}
+
+// Accessing something that isn't there is an error, unless ⎕missing is set to 'null'
+fn missing_access(description: String) -> ExecuteOutput {
+    match get_system_variable("⎕missing") {
+        ExecuteOutput::String (mode) if mode == "null" => ExecuteOutput::Null,
+        _ => panic!("Cannot access missing {}, set ⎕missing <- 'null' to get null instead", description)
+    }
 }
 
-fn execute_access_dict_with_string(lhs_map: HashMap<String, ExecuteOutput>, rhs_string: String) -> ExecuteOutput {
-    println!("Accessing dict {:?} with {:?}", lhs_map, rhs_string);
-    lhs_map.get(&rhs_string).unwrap().clone()
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    #[test]
+    #[should_panic(expected = "set ⎕missing <- 'null' to get null instead")]
+    fn rejects_missing_keys_by_default() {
+        evaluate("{ 'a': 1 }.'b'");
+    }
+
+    #[test]
+    fn gives_null_for_missing_keys_and_indices() {
+        let recs = "⎕missing <- 'null'\nrecs <- [ { 'age': 22, 'salary': 300 }, { 'age': 35 } ]\n";
+
+        assert_eq!(evaluate(&(recs.to_string() + "recs.'salary'")).to_string(), "[300, null]");
+        assert_eq!(evaluate(&(recs.to_string() + "(recs.'salary') + 1")).to_string(), "[301, null]");
+        assert_eq!(evaluate(&(recs.to_string() + "[1, 2].5")).to_string(), "null");
+    }
+}
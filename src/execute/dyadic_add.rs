@@ -1,18 +1,31 @@
+use super::temporal::{is_temporal, apply_temporal, execute_temporal_add};
 use super::structures::ExecuteOutput;
-use std::collections::HashMap;
-use crate::parse::structures::Numeric;
+use indexmap::IndexMap;
+use super::dict_keys::align_dicts;
+use super::overflow::check_overflow;
+use crate::parse::structures::{Numeric, DyadicVerb, MapKey};
 
 pub fn execute_add(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
     match (lhs, rhs) {
+        // Nulls propagate through the verb
+        (ExecuteOutput::Null, _) | (_, ExecuteOutput::Null) => ExecuteOutput::Null,
+        // Dates, times and durations, applied to every value of an array
+        (lhs, rhs) if is_temporal(&lhs) || is_temporal(&rhs) => apply_temporal(lhs, rhs, execute_add, execute_temporal_add),
         // Adding two arrays of numbers
         (ExecuteOutput::Array (lhs_array), ExecuteOutput::Array (rhs_array)) => execute_add_arrays(lhs_array, rhs_array),
         // Adding an array + number
         (ExecuteOutput::Array (int_array), ExecuteOutput::Numeric (numeric_val))
             | (ExecuteOutput::Numeric (numeric_val), ExecuteOutput::Array(int_array)) => execute_add_array_and_numeric(int_array, numeric_val),
         // Adding two numbers
-        (ExecuteOutput::Numeric (lhs_val), ExecuteOutput::Numeric (rhs_val)) => ExecuteOutput::Numeric(lhs_val + rhs_val),
+        (ExecuteOutput::Numeric (lhs_val), ExecuteOutput::Numeric (rhs_val)) => ExecuteOutput::Numeric(check_overflow(lhs_val + rhs_val)),
         // Adding two dicts
         (ExecuteOutput::Map (lhs_map), ExecuteOutput::Map (rhs_map)) => execute_add_dicts(lhs_map, rhs_map),
+        // Adding a dict + number
+        (ExecuteOutput::Map (dict), ExecuteOutput::Numeric (numeric_val))
+            | (ExecuteOutput::Numeric (numeric_val), ExecuteOutput::Map (dict)) => execute_add_dict_and_numeric(dict, numeric_val),
+        // Adding a dict to each record of an array
+        (ExecuteOutput::Array (records), ExecuteOutput::Map (dict))
+            | (ExecuteOutput::Map (dict), ExecuteOutput::Array (records)) => execute_add_records_and_dict(records, dict),
         (lhs_other, rhs_other) => panic!("Cannot add pair ({:?}, {:?})", lhs_other, rhs_other)
     }
 }
@@ -34,16 +47,11 @@ fn execute_add_arrays(lhs_array: Vec<ExecuteOutput>, rhs_array: Vec<ExecuteOutpu
     ExecuteOutput::Array(output)
 }
 
-fn execute_add_dicts(lhs_map: HashMap<String, ExecuteOutput>, rhs_map: HashMap<String, ExecuteOutput>) -> ExecuteOutput {
-    if lhs_map.len() != rhs_map.len() {
-        panic!("Cannot add dicts of different lengths: {:?} vs {:?}", lhs_map.len(), rhs_map.len());
-    }
-
-    let mut output: HashMap<String, ExecuteOutput> = HashMap::new();
+fn execute_add_dicts(lhs_map: IndexMap<MapKey, ExecuteOutput>, rhs_map: IndexMap<MapKey, ExecuteOutput>) -> ExecuteOutput {
+    let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
 
-    for (key, value) in lhs_map {
-        let rhs_value = rhs_map.get(&key).unwrap().clone();
-        output.insert(key, execute_add(value, rhs_value));
+    for (key, lhs_value, rhs_value) in align_dicts(DyadicVerb::Add, lhs_map, rhs_map) {
+        output.insert(key, execute_add(lhs_value, rhs_value));
     }
 
     ExecuteOutput::Map(output)
@@ -56,4 +64,40 @@ fn execute_add_array_and_numeric(int_array:  Vec<ExecuteOutput>, int_val: Numeri
     let output = int_array.into_iter().map(|x| execute_add(x, int_val.clone())).collect();
 
     ExecuteOutput::Array(output)
-}
\ No newline at end of file
+}
+
+fn execute_add_dict_and_numeric(dict: IndexMap<MapKey, ExecuteOutput>, numeric_val: Numeric) -> ExecuteOutput {
+    let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+    let numeric_val = ExecuteOutput::Numeric(numeric_val);
+    for (key, val) in dict {
+        output.insert(key, execute_add(val, numeric_val.clone()));
+    }
+
+    ExecuteOutput::Map(output)
+}
+
+fn execute_add_records_and_dict(records: Vec<ExecuteOutput>, dict: IndexMap<MapKey, ExecuteOutput>) -> ExecuteOutput {
+    let dict = ExecuteOutput::Map(dict);
+
+    ExecuteOutput::Array(records.into_iter().map(|record| execute_add(record, dict.clone())).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    #[test]
+    fn adds_numbers_to_arrays_and_maps() {
+        assert_eq!(evaluate("[1, 2] + 1").to_string(), "[2, 3]");
+        assert_eq!(evaluate("1 + [1, 2.5]").to_string(), "[2, 3.5]");
+        assert_eq!(evaluate("{ 'a': 1, 'b': null } + 1").to_string(), "{'a': 2, 'b': null}");
+        assert_eq!(evaluate("0.5 + { 'a': 1 }").to_string(), "{'a': 1.5}");
+    }
+
+    #[test]
+    fn adds_maps_to_each_record() {
+        assert_eq!(evaluate("[{ 'a': 1 }, { 'a': 2 }] + { 'a': 10 }").to_string(), "[{'a': 11}, {'a': 12}]");
+        assert_eq!(evaluate("{ 'a': 10 } + [{ 'a': 1 }, { 'a': 2 }]").to_string(), "[{'a': 11}, {'a': 12}]");
+    }
+}
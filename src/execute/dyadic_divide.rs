@@ -1,15 +1,31 @@
+use super::temporal::{is_temporal, apply_temporal, execute_temporal_divide};
 use super::structures::ExecuteOutput;
-use crate::parse::structures::Numeric;
-use std::collections::HashMap;
+use super::dict_keys::align_dicts;
+use super::overflow::check_overflow;
+use super::system_variables::get_system_variable;
+use crate::parse::decimal::Rounding;
+use crate::parse::structures::{Numeric, DyadicVerb, MapKey};
+use indexmap::IndexMap;
 
 pub fn execute_divide(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
     match (lhs, rhs) {
+        // Nulls propagate through the verb
+        (ExecuteOutput::Null, _) | (_, ExecuteOutput::Null) => ExecuteOutput::Null,
+        // Dates, times and durations, applied to every value of an array
+        (lhs, rhs) if is_temporal(&lhs) || is_temporal(&rhs) => apply_temporal(lhs, rhs, execute_divide, execute_temporal_divide),
         // Divide an array by an array
         (ExecuteOutput::Array (lhs_array), ExecuteOutput::Array (rhs_array)) => execute_divide_array_by_array(lhs_array, rhs_array),
-        // Divide a dictionary by a number
+        // Divide a dictionary by a dictionary
+        (ExecuteOutput::Map (lhs_map), ExecuteOutput::Map (rhs_map)) => execute_divide_dicts(lhs_map, rhs_map),
+        // Divide each record of an array by a dictionary, e.g. the total record, or a dictionary by each record
+        (ExecuteOutput::Array (records), ExecuteOutput::Map (rhs_map)) => execute_divide_records_by_dict(records, rhs_map),
+        (ExecuteOutput::Map (lhs_map), ExecuteOutput::Array (records)) => execute_divide_dict_by_records(lhs_map, records),
+        // Divide a dictionary by a number, or a number by a dictionary
         (ExecuteOutput::Map (lhs_map), ExecuteOutput::Numeric (rhs_numeric)) => execute_divide_dict_by_numeric(lhs_map, rhs_numeric),
-        // Divide an array by a number
+        (ExecuteOutput::Numeric (lhs_numeric), ExecuteOutput::Map (rhs_map)) => execute_divide_numeric_by_dict(lhs_numeric, rhs_map),
+        // Divide an array by a number, or a number by an array
         (ExecuteOutput::Array (lhs_array), ExecuteOutput::Numeric (numeric)) => execute_divide_array_by_numeric(lhs_array, numeric),
+        (ExecuteOutput::Numeric (numeric), ExecuteOutput::Array (rhs_array)) => execute_divide_numeric_by_array(numeric, rhs_array),
         // Divide a number by a number
         (ExecuteOutput::Numeric (lhs_numeric), ExecuteOutput::Numeric (rhs_numeric)) => execute_divide_numeric_by_numeric(lhs_numeric, rhs_numeric),
         (lhs_other, rhs_other) => panic!("Cannot divide pair ({:?}, {:?})", lhs_other, rhs_other)
@@ -28,6 +44,12 @@ fn execute_divide_array_by_numeric(lhs_array: Vec<ExecuteOutput>, numeric: Numer
     ExecuteOutput::Array(output)
 }
 
+fn execute_divide_numeric_by_array(numeric: Numeric, rhs_array: Vec<ExecuteOutput>) -> ExecuteOutput {
+    let numeric = ExecuteOutput::Numeric(numeric);
+
+    ExecuteOutput::Array(rhs_array.into_iter().map(|val| execute_divide(numeric.clone(), val)).collect())
+}
+
 fn execute_divide_array_by_array(lhs_array: Vec<ExecuteOutput>, rhs_array: Vec<ExecuteOutput>) -> ExecuteOutput {
     if lhs_array.len() != rhs_array.len() {
         panic!("Cannot divide two arrays of different size {:?} vs {:?}", lhs_array, rhs_array);
@@ -46,8 +68,18 @@ fn execute_divide_array_by_array(lhs_array: Vec<ExecuteOutput>, rhs_array: Vec<E
     ExecuteOutput::Array(output)
 }
 
-fn execute_divide_dict_by_numeric(lhs_map: HashMap<String, ExecuteOutput>, numeric: Numeric) -> ExecuteOutput {
-    let mut output: HashMap<String, ExecuteOutput> = HashMap::new();
+fn execute_divide_dicts(lhs_map: IndexMap<MapKey, ExecuteOutput>, rhs_map: IndexMap<MapKey, ExecuteOutput>) -> ExecuteOutput {
+    let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+    for (key, lhs_value, rhs_value) in align_dicts(DyadicVerb::Divide, lhs_map, rhs_map) {
+        output.insert(key, execute_divide(lhs_value, rhs_value));
+    }
+
+    ExecuteOutput::Map(output)
+}
+
+fn execute_divide_dict_by_numeric(lhs_map: IndexMap<MapKey, ExecuteOutput>, numeric: Numeric) -> ExecuteOutput {
+    let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
 
     // Wrap numeric in an ExecuteOutput so it can be passed back into calculate_divide
     let numeric = ExecuteOutput::Numeric(numeric);
@@ -58,18 +90,90 @@ fn execute_divide_dict_by_numeric(lhs_map: HashMap<String, ExecuteOutput>, numer
     ExecuteOutput::Map(output)
 }
 
+fn execute_divide_numeric_by_dict(numeric: Numeric, rhs_map: IndexMap<MapKey, ExecuteOutput>) -> ExecuteOutput {
+    let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+    let numeric = ExecuteOutput::Numeric(numeric);
+    for (key, val) in rhs_map {
+        output.insert(key, execute_divide(numeric.clone(), val));
+    }
+
+    ExecuteOutput::Map(output)
+}
+
+fn execute_divide_records_by_dict(records: Vec<ExecuteOutput>, rhs_map: IndexMap<MapKey, ExecuteOutput>) -> ExecuteOutput {
+    let rhs_map = ExecuteOutput::Map(rhs_map);
+
+    ExecuteOutput::Array(records.into_iter().map(|record| execute_divide(record, rhs_map.clone())).collect())
+}
+
+fn execute_divide_dict_by_records(lhs_map: IndexMap<MapKey, ExecuteOutput>, records: Vec<ExecuteOutput>) -> ExecuteOutput {
+    let lhs_map = ExecuteOutput::Map(lhs_map);
+
+    ExecuteOutput::Array(records.into_iter().map(|record| execute_divide(lhs_map.clone(), record)).collect())
+}
+
+// Dividing by zero is an error for every kind of number, as a decimal has no infinity to give
 fn execute_divide_numeric_by_numeric(lhs_numeric: Numeric, rhs_numeric: Numeric) -> ExecuteOutput {
-    let lhs_float = match lhs_numeric {
-        Numeric::Float(x) => x,
-        Numeric::Int(x) => x as f64
+    if rhs_numeric.as_float() == 0.0 {
+        panic!("DOMAIN ERROR: cannot divide {:?} by zero", lhs_numeric);
+    }
+
+    let result = match (&lhs_numeric, &rhs_numeric) {
+        (Numeric::Decimal(_), _) | (_, Numeric::Decimal(_)) => execute_divide_decimal(lhs_numeric, rhs_numeric),
+        _ => lhs_numeric / rhs_numeric
     };
 
-    let rhs_float = match rhs_numeric {
-        Numeric::Float(x) => x,
-        Numeric::Int(x) => x as f64
+    ExecuteOutput::Numeric(check_overflow(result))
+}
+
+// Decimal division rounds to ⎕scale decimal places if set, otherwise it's exact where the result
+// terminates, using the ⎕round rounding mode which defaults to 'half_even'
+fn execute_divide_decimal(lhs_numeric: Numeric, rhs_numeric: Numeric) -> Numeric {
+    let scale = match get_system_variable("⎕scale") {
+        ExecuteOutput::Null => None,
+        ExecuteOutput::Numeric (Numeric::Int(x)) if x >= 0 => Some(x as u32),
+        other => panic!("⎕scale must be a non-negative int, found {:?}", other)
     };
 
-    let result: f64 = lhs_float / rhs_float;
+    let rounding = match get_system_variable("⎕round") {
+        ExecuteOutput::Null => Rounding::HalfEven,
+        ExecuteOutput::String (mode) => match Rounding::from_name(&mode) {
+            Some (rounding) => rounding,
+            None => panic!("Unknown rounding mode ⎕round <- {:?}, expected one of 'half_even', 'half_up', 'down', 'up', 'floor' or 'ceiling'", mode)
+        },
+        other => panic!("⎕round must be a string, found {:?}", other)
+    };
+
+    lhs_numeric.divide_decimal(&rhs_numeric, scale, rounding)
+}
 
-    ExecuteOutput::Numeric(Numeric::Float(result))
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    #[test]
+    fn divides_numbers_and_maps() {
+        assert_eq!(evaluate("{ 'a': 6, 'b': 3 } ÷ 2").to_string(), "{'a': 3, 'b': 1.5}");
+        assert_eq!(evaluate("12 ÷ { 'a': 3, 'b': null }").to_string(), "{'a': 4, 'b': null}");
+        assert_eq!(evaluate("12 ÷ [3, 8]").to_string(), "[4, 1.5]");
+    }
+
+    #[test]
+    #[should_panic(expected = "DOMAIN ERROR: cannot divide Int(1) by zero")]
+    fn rejects_dividing_ints_by_zero() {
+        evaluate("1 ÷ 0");
+    }
+
+    #[test]
+    #[should_panic(expected = "DOMAIN ERROR: cannot divide Decimal(1.50) by zero")]
+    fn rejects_dividing_decimals_by_zero() {
+        evaluate("1.50d ÷ 0.0");
+    }
+
+    #[test]
+    fn divides_each_record_by_maps() {
+        assert_eq!(evaluate("[{ 'a': 1, 'b': 6 }, { 'a': 3, 'b': 2 }] ÷ { 'a': 4, 'b': 8 }").to_string(), "[{'a': 0.25, 'b': 0.75}, {'a': 0.75, 'b': 0.25}]");
+        assert_eq!(evaluate("{ 'a': 12 } ÷ [{ 'a': 3 }, { 'a': 4 }]").to_string(), "[{'a': 4}, {'a': 3}]");
+    }
+}
@@ -1,17 +1,28 @@
-use crate::parse::structures::Numeric;
+use super::temporal::{is_temporal, apply_temporal, compare_temporal};
+use std::cmp::Ordering;
+use crate::parse::structures::{Numeric, DyadicVerb, MapKey};
+use super::dict_keys::align_dicts;
 use super::structures::ExecuteOutput;
-use std::collections::HashMap;
+use indexmap::IndexMap;
 
 // TODO: ideally it should be that you can always compare to an item at least one 'rank' less than the current
 // e.g. can do [ [...], [...], [...] ] = [...], or [ [...], [...] ] = 5 or [ 1, 2, 3 ] = 5
 pub fn execute_equals(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
     match (lhs, rhs) {
+        // Nulls propagate through the verb
+        (ExecuteOutput::Null, _) | (_, ExecuteOutput::Null) => ExecuteOutput::Null,
+        // Dates, times and durations, applied to every value of an array
+        (lhs, rhs) if is_temporal(&lhs) || is_temporal(&rhs) => apply_temporal(lhs, rhs, execute_equals, |lhs, rhs| ExecuteOutput::Numeric(Numeric::Int((compare_temporal(&lhs, &rhs) == Ordering::Equal) as i64))),
         (ExecuteOutput::Array (lhs_array), ExecuteOutput::Array (rhs_array)) => execute_equals_arrays(lhs_array, rhs_array),
-        (ExecuteOutput::Array (array), ExecuteOutput::Numeric (numeric) )
-            | ( ExecuteOutput::Numeric (numeric), ExecuteOutput::Array (array) ) => execute_equals_array_and_numeric(array, numeric),
-        (ExecuteOutput::Map (map), ExecuteOutput::Numeric (numeric)) 
-            | (ExecuteOutput::Numeric (numeric), ExecuteOutput::Map (map)) => execute_equals_map_and_numeric(map, numeric),
+        (ExecuteOutput::Array (array), scalar @ (ExecuteOutput::Numeric (_) | ExecuteOutput::String (_)))
+            | (scalar @ (ExecuteOutput::Numeric (_) | ExecuteOutput::String (_)), ExecuteOutput::Array (array)) => execute_equals_array_and_scalar(array, scalar),
+        (ExecuteOutput::Map (lhs_map), ExecuteOutput::Map (rhs_map)) => execute_equals_maps(lhs_map, rhs_map),
+        (ExecuteOutput::Map (map), scalar @ (ExecuteOutput::Numeric (_) | ExecuteOutput::String (_)))
+            | (scalar @ (ExecuteOutput::Numeric (_) | ExecuteOutput::String (_)), ExecuteOutput::Map (map)) => execute_equals_map_and_scalar(map, scalar),
         (ExecuteOutput::Numeric (lhs_numeric), ExecuteOutput::Numeric (rhs_numeric)) => execute_equals_numerics(lhs_numeric, rhs_numeric),
+        (ExecuteOutput::String (lhs_string), ExecuteOutput::String (rhs_string)) => bool_mask(lhs_string == rhs_string),
+        // A string is never equal to a number
+        (ExecuteOutput::String (_), ExecuteOutput::Numeric (_)) | (ExecuteOutput::Numeric (_), ExecuteOutput::String (_)) => bool_mask(false),
         (lhs_other, rhs_other) => panic!("Cannot perform equals on {:?} = {:?}", lhs_other, rhs_other)
     }
 }
@@ -33,23 +44,31 @@ fn execute_equals_arrays(lhs_array: Vec<ExecuteOutput>, rhs_array: Vec<ExecuteOu
     ExecuteOutput::Array(output)
 }
 
-fn execute_equals_map_and_numeric(map: HashMap<String, ExecuteOutput>, numeric: Numeric) -> ExecuteOutput {
-    let mut output: HashMap<String, ExecuteOutput> = HashMap::new();
+fn execute_equals_maps(lhs_map: IndexMap<MapKey, ExecuteOutput>, rhs_map: IndexMap<MapKey, ExecuteOutput>) -> ExecuteOutput {
+    let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+    for (key, lhs_value, rhs_value) in align_dicts(DyadicVerb::Equals, lhs_map, rhs_map) {
+        output.insert(key, execute_equals(lhs_value, rhs_value));
+    }
+
+    ExecuteOutput::Map(output)
+}
+
+fn execute_equals_map_and_scalar(map: IndexMap<MapKey, ExecuteOutput>, scalar: ExecuteOutput) -> ExecuteOutput {
+    let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
 
-    let numeric = ExecuteOutput::Numeric(numeric);
     for (key, value) in map {
-        output.insert(key, execute_equals(value, numeric.clone()));
+        output.insert(key, execute_equals(value, scalar.clone()));
     }
 
     ExecuteOutput::Map(output)
 }
 
-fn execute_equals_array_and_numeric(array: Vec<ExecuteOutput>, numeric: Numeric) -> ExecuteOutput {
+fn execute_equals_array_and_scalar(array: Vec<ExecuteOutput>, scalar: ExecuteOutput) -> ExecuteOutput {
     let mut output: Vec<ExecuteOutput> = Vec::new();
 
-    let numeric = ExecuteOutput::Numeric(numeric);
     for val in array {
-        output.push(execute_equals(val, numeric.clone()));
+        output.push(execute_equals(val, scalar.clone()));
     }
 
     ExecuteOutput::Array(output)
@@ -63,4 +82,53 @@ fn execute_equals_numerics(lhs_numeric: Numeric, rhs_numeric: Numeric) -> Execut
     };
 
     ExecuteOutput::Numeric(equal)
-}
\ No newline at end of file
+}
+
+// Not equals is the negation of equals, nulls still propagate
+pub fn execute_not_equals(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    negate_mask(execute_equals(lhs, rhs))
+}
+
+fn negate_mask(mask: ExecuteOutput) -> ExecuteOutput {
+    match mask {
+        ExecuteOutput::Numeric (Numeric::Int(x)) => bool_mask(x == 0),
+        ExecuteOutput::Array (arr) => ExecuteOutput::Array(arr.into_iter().map(negate_mask).collect()),
+        ExecuteOutput::Map (dict) => {
+            let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+            for (key, value) in dict {
+                output.insert(key, negate_mask(value));
+            }
+
+            ExecuteOutput::Map(output)
+        },
+        other => other
+    }
+}
+
+fn bool_mask(val: bool) -> ExecuteOutput {
+    ExecuteOutput::Numeric(Numeric::Int(val as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    const VALS: &str = "vals <- [ { 'dept': 'sales', 'n': 1 }, { 'dept': 'ops', 'n': 2 }, { 'dept': null, 'n': 3 } ]\n";
+
+    #[test]
+    fn compares_strings() {
+        assert_eq!(evaluate("'a' = 'a'").to_string(), "1");
+        assert_eq!(evaluate("'a' ≠ 'A'").to_string(), "1");
+        assert_eq!(evaluate("'a' = 1").to_string(), "0");
+        assert_eq!(evaluate("['a', 'b'] = ['a', 'c']").to_string(), "[1, 0]");
+        assert_eq!(evaluate("{ 'a': 'x', 'b': 'y' } = { 'a': 'x', 'b': 'z' }").to_string(), "{'a': 1, 'b': 0}");
+    }
+
+    #[test]
+    fn filters_records_by_string_fields() {
+        assert_eq!(evaluate(&(VALS.to_string() + "(vals.'dept') = 'sales'")).to_string(), "[1, 0, null]");
+        assert_eq!(evaluate(&(VALS.to_string() + "(((vals.'dept') = 'sales') / vals).'n'")).to_string(), "[1]");
+        assert_eq!(evaluate(&(VALS.to_string() + "(((vals.'dept') ≠ 'sales') / vals).'n'")).to_string(), "[2]");
+    }
+}
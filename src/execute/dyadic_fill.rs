@@ -0,0 +1,91 @@
+use super::structures::ExecuteOutput;
+use crate::parse::structures::MapKey;
+use indexmap::IndexMap;
+
+// Replace nulls on the lhs with the rhs value. A rhs map acts as a set of defaults, filling keys
+// that are null or missing from the lhs map, so sparse records can be completed in one go
+pub fn execute_fill(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    match (lhs, rhs) {
+        (ExecuteOutput::Null, rhs_value) => rhs_value,
+        // Fill two arrays pairwise
+        (ExecuteOutput::Array (lhs_array), ExecuteOutput::Array (rhs_array)) => execute_fill_arrays(lhs_array, rhs_array),
+        // Fill every value of an array with the same rhs
+        (ExecuteOutput::Array (lhs_array), rhs_value) => {
+            let output = lhs_array.into_iter().map(|x| execute_fill(x, rhs_value.clone())).collect();
+
+            ExecuteOutput::Array(output)
+        },
+        // Fill missing and null keys of a map with defaults from another map
+        (ExecuteOutput::Map (lhs_map), ExecuteOutput::Map (rhs_map)) => execute_fill_dicts(lhs_map, rhs_map),
+        // Fill every value of a map with the same rhs
+        (ExecuteOutput::Map (lhs_map), rhs_value) => {
+            let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+            for (key, value) in lhs_map {
+                output.insert(key, execute_fill(value, rhs_value.clone()));
+            }
+
+            ExecuteOutput::Map(output)
+        },
+        // Nothing to fill
+        (lhs_value, _) => lhs_value
+    }
+}
+
+fn execute_fill_arrays(lhs_array: Vec<ExecuteOutput>, rhs_array: Vec<ExecuteOutput>) -> ExecuteOutput {
+    if lhs_array.len() != rhs_array.len() {
+        panic!("Cannot fill arrays of different lengths {:?} vs {:?}", lhs_array.len(), rhs_array.len());
+    }
+
+    let mut output: Vec<ExecuteOutput> = Vec::new();
+
+    for (lhs_val, rhs_val) in lhs_array.into_iter().zip(rhs_array) {
+        output.push(execute_fill(lhs_val, rhs_val));
+    }
+
+    ExecuteOutput::Array(output)
+}
+
+fn execute_fill_dicts(lhs_map: IndexMap<MapKey, ExecuteOutput>, rhs_map: IndexMap<MapKey, ExecuteOutput>) -> ExecuteOutput {
+    let mut output: IndexMap<MapKey, ExecuteOutput> = lhs_map;
+
+    for (key, default) in rhs_map {
+        match output.get_index_of(&key) {
+            // Fill in place so the key keeps its position from the lhs
+            Some (index) => {
+                let lhs_value = std::mem::replace(&mut output[index], ExecuteOutput::Null);
+                output[index] = execute_fill(lhs_value, default);
+            },
+            None => {
+                output.insert(key, default);
+            }
+        };
+    }
+
+    ExecuteOutput::Map(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    #[test]
+    fn fills_nulls_in_values_arrays_and_maps() {
+        assert_eq!(evaluate("null fill 0").to_string(), "0");
+        assert_eq!(evaluate("[1, null, 3] fill 0").to_string(), "[1, 0, 3]");
+        assert_eq!(evaluate("[null, 2] fill [5, 6]").to_string(), "[5, 2]");
+        assert_eq!(evaluate("{ 'a': null, 'b': 2 } fill 0").to_string(), "{'a': 0, 'b': 2}");
+    }
+
+    #[test]
+    fn fills_records_with_defaults() {
+        assert_eq!(evaluate("[{ 'age': 22 }, { 'age': null, 'salary': 1 }] fill { 'age': 0, 'salary': 300 }").to_string(), "[{'age': 22, 'salary': 300}, {'age': 0, 'salary': 1}]");
+        assert_eq!(evaluate("{ 'a': { 'x': null } } fill { 'a': { 'x': 1, 'y': 2 } }").to_string(), "{'a': {'x': 1, 'y': 2}}");
+    }
+
+    #[test]
+    #[should_panic(expected = "different lengths")]
+    fn rejects_arrays_of_different_lengths() {
+        evaluate("[null, 2] fill [1]");
+    }
+}
@@ -1,9 +1,20 @@
+use super::temporal::{is_temporal, apply_temporal, compare_temporal};
+use std::cmp::Ordering;
 use super::structures::ExecuteOutput;
-use crate::parse::structures::Numeric;
+use super::dict_keys::align_dicts;
+use crate::parse::structures::{Numeric, DyadicVerb, MapKey};
+use indexmap::IndexMap;
 
 pub fn execute_greaterthan(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
     match (lhs, rhs) {
+        // Nulls propagate through the verb
+        (ExecuteOutput::Null, _) | (_, ExecuteOutput::Null) => ExecuteOutput::Null,
+        // Dates, times and durations, applied to every value of an array
+        (lhs, rhs) if is_temporal(&lhs) || is_temporal(&rhs) => apply_temporal(lhs, rhs, execute_greaterthan, |lhs, rhs| ExecuteOutput::Numeric(Numeric::Int((compare_temporal(&lhs, &rhs) == Ordering::Greater) as i64))),
         (ExecuteOutput::Array (lhs_array), ExecuteOutput::Numeric (rhs_numeric)) => execute_array_greaterthan_numeric(lhs_array, rhs_numeric),
+        (ExecuteOutput::Numeric (lhs_numeric), ExecuteOutput::Array (rhs_array)) => execute_numeric_greaterthan_array(lhs_numeric, rhs_array),
+        (ExecuteOutput::Array (lhs_array), ExecuteOutput::Array (rhs_array)) => execute_array_greaterthan_array(lhs_array, rhs_array),
+        (ExecuteOutput::Map (lhs_map), ExecuteOutput::Map (rhs_map)) => execute_map_greaterthan_map(lhs_map, rhs_map),
         (ExecuteOutput::Numeric (lhs_numeric), ExecuteOutput::Numeric (rhs_numeric)) => execute_numeric_greaterthan_numeric(lhs_numeric, rhs_numeric),
         (lhs_other, rhs_other) => panic!("Cannot calculate > of {:?} / {:?}", lhs_other, rhs_other)
     }
@@ -21,6 +32,31 @@ fn execute_array_greaterthan_numeric(lhs_array: Vec<ExecuteOutput>, rhs_numeric:
     ExecuteOutput::Array(output)
 }
 
+fn execute_numeric_greaterthan_array(lhs_numeric: Numeric, rhs_array: Vec<ExecuteOutput>) -> ExecuteOutput {
+    let lhs_numeric = ExecuteOutput::Numeric(lhs_numeric);
+
+    ExecuteOutput::Array(rhs_array.into_iter().map(|val| execute_greaterthan(lhs_numeric.clone(), val)).collect())
+}
+
+// Arrays are compared element by element, so must be the same length
+fn execute_array_greaterthan_array(lhs_array: Vec<ExecuteOutput>, rhs_array: Vec<ExecuteOutput>) -> ExecuteOutput {
+    if lhs_array.len() != rhs_array.len() {
+        panic!("Cannot calculate > of arrays of different lengths {} and {}", lhs_array.len(), rhs_array.len());
+    }
+
+    ExecuteOutput::Array(lhs_array.into_iter().zip(rhs_array).map(|(lhs, rhs)| execute_greaterthan(lhs, rhs)).collect())
+}
+
+fn execute_map_greaterthan_map(lhs_map: IndexMap<MapKey, ExecuteOutput>, rhs_map: IndexMap<MapKey, ExecuteOutput>) -> ExecuteOutput {
+    let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+    for (key, lhs_value, rhs_value) in align_dicts(DyadicVerb::GreaterThan, lhs_map, rhs_map) {
+        output.insert(key, execute_greaterthan(lhs_value, rhs_value));
+    }
+
+    ExecuteOutput::Map(output)
+}
+
 fn execute_numeric_greaterthan_numeric(lhs_numeric: Numeric, rhs_numeric: Numeric) -> ExecuteOutput {
     // TODO: do we want a bool type?
     let out = if lhs_numeric > rhs_numeric {
@@ -30,4 +66,22 @@ fn execute_numeric_greaterthan_numeric(lhs_numeric: Numeric, rhs_numeric: Numeri
     };
 
     ExecuteOutput::Numeric(out)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    #[test]
+    fn compares_numbers_with_arrays() {
+        assert_eq!(evaluate("[1, 5, 3] > 2").to_string(), "[0, 1, 1]");
+        assert_eq!(evaluate("2 > [1, 5, 2]").to_string(), "[1, 0, 0]");
+        assert_eq!(evaluate("[1, 5, null] > [2, 4, 1]").to_string(), "[0, 1, null]");
+    }
+
+    #[test]
+    #[should_panic(expected = "different lengths")]
+    fn rejects_arrays_of_different_lengths() {
+        evaluate("[1, 2] > [1, 2, 3]");
+    }
+}
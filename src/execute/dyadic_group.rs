@@ -0,0 +1,65 @@
+use super::structures::ExecuteOutput;
+use super::dyadic_access::execute_access;
+use super::map_key::try_map_key;
+use crate::parse::structures::MapKey;
+use indexmap::IndexMap;
+
+// Group an array of records by the value of a key, giving a map of each value to the records
+// with it. An array of keys groups by all of them, with the values as a composite key, e.g.
+// records group ['year', 'dept'] gives { [2021, 'sales']: [...], [2022, 'sales']: [...] }. Values that
+// can't be map keys, such as null for a missing key, are an error naming the record
+pub fn execute_group(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    let records = match lhs {
+        ExecuteOutput::Array (arr) => arr,
+        other => panic!("Cannot group {:?}, must be an array of maps", other)
+    };
+
+    let mut groups: IndexMap<MapKey, Vec<ExecuteOutput>> = IndexMap::new();
+
+    for record in records {
+        let group_key = match &rhs {
+            ExecuteOutput::Array (keys) => ExecuteOutput::Array(keys.iter().map(|key| execute_access(record.clone(), key.clone())).collect()),
+            key => execute_access(record.clone(), key.clone())
+        };
+
+        let group_key = match try_map_key(group_key.clone()) {
+            Some (group_key) => group_key,
+            None => panic!("Cannot group record {} by {}, its value {} must be a string, int, date or array of these", record, rhs, group_key)
+        };
+
+        groups.entry(group_key).or_default().push(record);
+    }
+
+    let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+    for (key, group) in groups {
+        output.insert(key, ExecuteOutput::Array(group));
+    }
+
+    ExecuteOutput::Map(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    #[test]
+    fn groups_by_keys() {
+        let records = "recs <- [{ 'year': 2021, 'dept': 'a' }, { 'year': 2022, 'dept': 'a' }, { 'year': 2021, 'dept': 'b' }]\n";
+
+        assert_eq!(evaluate(&(records.to_string() + "(recs group 'year').2022")).to_string(), "[{'year': 2022, 'dept': 'a'}]");
+        assert_eq!(evaluate(&(records.to_string() + "(recs group ['year', 'dept']).[2021, 'b']")).to_string(), "[{'year': 2021, 'dept': 'b'}]");
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot group record {'year': null} by 'year', its value null")]
+    fn rejects_null_keys() {
+        evaluate("[{ 'year': 2021 }, { 'year': null }] group 'year'");
+    }
+
+    #[test]
+    #[should_panic(expected = "its value 2.5 must be")]
+    fn rejects_float_keys() {
+        evaluate("[{ 'score': 2.5 }] group 'score'");
+    }
+}
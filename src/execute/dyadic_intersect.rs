@@ -0,0 +1,63 @@
+use super::structures::ExecuteOutput;
+use super::execute_dyadic::execute_dyadic_op;
+use crate::parse::structures::{DyadicVerb, MapKey};
+use indexmap::IndexMap;
+
+// Intersect two maps. Keys present on only one side are dropped, keys present on both sides are
+// combined with the combine verb, e.g. ⊢ keeps the rhs value and + adds them
+pub fn execute_intersect(lhs: ExecuteOutput, rhs: ExecuteOutput, combine_verb: DyadicVerb) -> ExecuteOutput {
+    match (lhs, rhs) {
+        // Intersect two maps
+        (ExecuteOutput::Map (lhs_map), ExecuteOutput::Map (rhs_map)) => execute_intersect_dicts(lhs_map, rhs_map, combine_verb),
+        // Intersect two arrays of maps pairwise
+        (ExecuteOutput::Array (lhs_array), ExecuteOutput::Array (rhs_array)) => execute_intersect_arrays(lhs_array, rhs_array, combine_verb),
+        // Intersect every map in an array with a single map, keeping the sides in order
+        (ExecuteOutput::Array (lhs_array), ExecuteOutput::Map (rhs_map)) => {
+            let rhs_map = ExecuteOutput::Map(rhs_map);
+            let output = lhs_array.into_iter().map(|x| execute_intersect(x, rhs_map.clone(), combine_verb.clone())).collect();
+
+            ExecuteOutput::Array(output)
+        },
+        (ExecuteOutput::Map (lhs_map), ExecuteOutput::Array (rhs_array)) => {
+            let lhs_map = ExecuteOutput::Map(lhs_map);
+            let output = rhs_array.into_iter().map(|x| execute_intersect(lhs_map.clone(), x, combine_verb.clone())).collect();
+
+            ExecuteOutput::Array(output)
+        },
+        (lhs_other, rhs_other) => panic!("Cannot intersect pair ({:?}, {:?})", lhs_other, rhs_other)
+    }
+}
+
+fn execute_intersect_arrays(lhs_array: Vec<ExecuteOutput>, rhs_array: Vec<ExecuteOutput>, combine_verb: DyadicVerb) -> ExecuteOutput {
+    if lhs_array.len() != rhs_array.len() {
+        panic!("Cannot intersect arrays of different lengths {:?} vs {:?}", lhs_array.len(), rhs_array.len());
+    }
+
+    let mut output: Vec<ExecuteOutput> = Vec::new();
+
+    for (lhs_val, rhs_val) in lhs_array.into_iter().zip(rhs_array) {
+        output.push(execute_intersect(lhs_val, rhs_val, combine_verb.clone()));
+    }
+
+    ExecuteOutput::Array(output)
+}
+
+fn execute_intersect_dicts(lhs_map: IndexMap<MapKey, ExecuteOutput>, mut rhs_map: IndexMap<MapKey, ExecuteOutput>, combine_verb: DyadicVerb) -> ExecuteOutput {
+    let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+    for (key, lhs_value) in lhs_map {
+        if let Some (rhs_value) = rhs_map.shift_remove(&key) {
+            output.insert(key, intersect_shared_values(lhs_value, rhs_value, combine_verb.clone()));
+        }
+    }
+
+    ExecuteOutput::Map(output)
+}
+
+fn intersect_shared_values(lhs: ExecuteOutput, rhs: ExecuteOutput, combine_verb: DyadicVerb) -> ExecuteOutput {
+    match (lhs, rhs) {
+        // Nested maps are intersected recursively rather than handed to the combine verb
+        (ExecuteOutput::Map (lhs_map), ExecuteOutput::Map (rhs_map)) => execute_intersect_dicts(lhs_map, rhs_map, combine_verb),
+        (lhs_other, rhs_other) => execute_dyadic_op(combine_verb, lhs_other, rhs_other)
+    }
+}
@@ -0,0 +1,61 @@
+use super::structures::ExecuteOutput;
+use super::dyadic_equals::execute_equals;
+use crate::parse::structures::{Numeric, MapKey};
+use indexmap::IndexMap;
+
+// Membership, giving 1 for each lhs value found in the rhs and 0 otherwise. The lhs pervades
+// through arrays and maps while the rhs is the set of values to look in, e.g. to filter records
+// (((vals.'dept') ∊ ['sales', 'ops']) / vals)
+pub fn execute_member(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    let candidates: Vec<ExecuteOutput> = match rhs {
+        ExecuteOutput::Array (arr) => arr,
+        ExecuteOutput::Map (dict) => dict.into_values().collect(),
+        scalar => vec![scalar]
+    };
+
+    execute_member_of(lhs, &candidates)
+}
+
+fn execute_member_of(lhs: ExecuteOutput, candidates: &[ExecuteOutput]) -> ExecuteOutput {
+    match lhs {
+        ExecuteOutput::Null => ExecuteOutput::Null,
+        ExecuteOutput::Array (arr) => ExecuteOutput::Array(arr.into_iter().map(|x| execute_member_of(x, candidates)).collect()),
+        ExecuteOutput::Map (dict) => {
+            let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+            for (key, value) in dict {
+                output.insert(key, execute_member_of(value, candidates));
+            }
+
+            ExecuteOutput::Map(output)
+        },
+        val => {
+            let found = candidates.iter().any(|candidate| {
+                // Only compare like with like, so arrays and maps in the rhs are never members
+                !matches!(candidate, ExecuteOutput::Array(_) | ExecuteOutput::Map(_))
+                    && matches!(execute_equals(val.clone(), candidate.clone()), ExecuteOutput::Numeric (Numeric::Int(1)))
+            });
+
+            ExecuteOutput::Numeric(Numeric::Int(found as i64))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    #[test]
+    fn tests_membership_of_each_value() {
+        assert_eq!(evaluate("2 ∊ [1, 2]").to_string(), "1");
+        assert_eq!(evaluate("'x' ∊ []").to_string(), "0");
+        assert_eq!(evaluate("[1, 'a', null] ∊ ['a', 1]").to_string(), "[1, 1, null]");
+    }
+
+    #[test]
+    fn filters_records() {
+        let vals = "vals <- [ { 'dept': 'sales', 'n': 1 }, { 'dept': 'ops', 'n': 2 }, { 'dept': 'hr', 'n': 3 } ]\n";
+
+        assert_eq!(evaluate(&(vals.to_string() + "(((vals.'dept') ∊ ['ops', 'hr']) / vals).'n'")).to_string(), "[2, 3]");
+    }
+}
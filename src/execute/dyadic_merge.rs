@@ -0,0 +1,70 @@
+use super::structures::ExecuteOutput;
+use super::execute_dyadic::execute_dyadic_op;
+use crate::parse::structures::{DyadicVerb, MapKey};
+use indexmap::IndexMap;
+
+// Union two maps. Keys present on only one side are copied over, keys present on both sides are
+// resolved with the combine verb, e.g. ⊢ keeps the rhs value, ⊣ keeps the lhs value, + adds them
+pub fn execute_merge(lhs: ExecuteOutput, rhs: ExecuteOutput, combine_verb: DyadicVerb) -> ExecuteOutput {
+    match (lhs, rhs) {
+        // Merge two maps
+        (ExecuteOutput::Map (lhs_map), ExecuteOutput::Map (rhs_map)) => execute_merge_dicts(lhs_map, rhs_map, combine_verb),
+        // Merge two arrays of maps pairwise
+        (ExecuteOutput::Array (lhs_array), ExecuteOutput::Array (rhs_array)) => execute_merge_arrays(lhs_array, rhs_array, combine_verb),
+        // Merge every map in an array with a single map, keeping the sides in order
+        (ExecuteOutput::Array (lhs_array), ExecuteOutput::Map (rhs_map)) => {
+            let rhs_map = ExecuteOutput::Map(rhs_map);
+            let output = lhs_array.into_iter().map(|x| execute_merge(x, rhs_map.clone(), combine_verb.clone())).collect();
+
+            ExecuteOutput::Array(output)
+        },
+        (ExecuteOutput::Map (lhs_map), ExecuteOutput::Array (rhs_array)) => {
+            let lhs_map = ExecuteOutput::Map(lhs_map);
+            let output = rhs_array.into_iter().map(|x| execute_merge(lhs_map.clone(), x, combine_verb.clone())).collect();
+
+            ExecuteOutput::Array(output)
+        },
+        (lhs_other, rhs_other) => panic!("Cannot merge pair ({:?}, {:?})", lhs_other, rhs_other)
+    }
+}
+
+fn execute_merge_arrays(lhs_array: Vec<ExecuteOutput>, rhs_array: Vec<ExecuteOutput>, combine_verb: DyadicVerb) -> ExecuteOutput {
+    if lhs_array.len() != rhs_array.len() {
+        panic!("Cannot merge arrays of different lengths {:?} vs {:?}", lhs_array.len(), rhs_array.len());
+    }
+
+    let mut output: Vec<ExecuteOutput> = Vec::new();
+
+    for (lhs_val, rhs_val) in lhs_array.into_iter().zip(rhs_array) {
+        output.push(execute_merge(lhs_val, rhs_val, combine_verb.clone()));
+    }
+
+    ExecuteOutput::Array(output)
+}
+
+fn execute_merge_dicts(lhs_map: IndexMap<MapKey, ExecuteOutput>, rhs_map: IndexMap<MapKey, ExecuteOutput>, combine_verb: DyadicVerb) -> ExecuteOutput {
+    let mut output: IndexMap<MapKey, ExecuteOutput> = lhs_map;
+
+    for (key, rhs_value) in rhs_map {
+        match output.get_index_of(&key) {
+            // Combine in place so the key keeps its position from the lhs
+            Some (index) => {
+                let lhs_value = std::mem::replace(&mut output[index], ExecuteOutput::Null);
+                output[index] = merge_colliding_values(lhs_value, rhs_value, combine_verb.clone());
+            },
+            None => {
+                output.insert(key, rhs_value);
+            }
+        };
+    }
+
+    ExecuteOutput::Map(output)
+}
+
+fn merge_colliding_values(lhs: ExecuteOutput, rhs: ExecuteOutput, combine_verb: DyadicVerb) -> ExecuteOutput {
+    match (lhs, rhs) {
+        // Nested maps are merged recursively rather than handed to the combine verb
+        (ExecuteOutput::Map (lhs_map), ExecuteOutput::Map (rhs_map)) => execute_merge_dicts(lhs_map, rhs_map, combine_verb),
+        (lhs_other, rhs_other) => execute_dyadic_op(combine_verb, lhs_other, rhs_other)
+    }
+}
@@ -1,23 +1,53 @@
+use super::temporal::{is_temporal, apply_temporal, execute_temporal_multiply};
 
 use super::structures::ExecuteOutput;
-use std::collections::HashMap;
-use crate::parse::structures::Numeric;
+use indexmap::IndexMap;
+use super::dict_keys::align_dicts;
+use super::overflow::check_overflow;
+use crate::parse::structures::{Numeric, DyadicVerb, MapKey};
 
 pub fn execute_multiply(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
     match (lhs, rhs) {
+        // Nulls propagate through the verb
+        (ExecuteOutput::Null, _) | (_, ExecuteOutput::Null) => ExecuteOutput::Null,
+        // Dates, times and durations, applied to every value of an array
+        (lhs, rhs) if is_temporal(&lhs) || is_temporal(&rhs) => apply_temporal(lhs, rhs, execute_multiply, execute_temporal_multiply),
         // Multiply two arrays of numbers
         (ExecuteOutput::Array (lhs_array), ExecuteOutput::Array (rhs_array)) => execute_multiply_arrays(lhs_array, rhs_array),
         // Multiply an array + number
         (ExecuteOutput::Array (int_array), ExecuteOutput::Numeric (numeric_val))
             | (ExecuteOutput::Numeric (numeric_val), ExecuteOutput::Array(int_array)) => execute_multiply_array_and_numeric(int_array, numeric_val),
         // Multiply two numbers
-        (ExecuteOutput::Numeric (lhs_val), ExecuteOutput::Numeric (rhs_val)) => ExecuteOutput::Numeric(lhs_val * rhs_val),
+        (ExecuteOutput::Numeric (lhs_val), ExecuteOutput::Numeric (rhs_val)) => ExecuteOutput::Numeric(check_overflow(lhs_val * rhs_val)),
         // Multiply two dicts
         (ExecuteOutput::Map (lhs_map), ExecuteOutput::Map (rhs_map)) => execute_multiply_dicts(lhs_map, rhs_map),
+        // Multiply a dict + number
+        (ExecuteOutput::Map (dict), ExecuteOutput::Numeric (numeric_val))
+            | (ExecuteOutput::Numeric (numeric_val), ExecuteOutput::Map (dict)) => execute_multiply_dict_and_numeric(dict, numeric_val),
+        // Multiply each record of an array by a dict
+        (ExecuteOutput::Array (records), ExecuteOutput::Map (dict))
+            | (ExecuteOutput::Map (dict), ExecuteOutput::Array (records)) => execute_multiply_records_and_dict(records, dict),
         (lhs_other, rhs_other) => panic!("Cannot multiply pair ({:?}, {:?})", lhs_other, rhs_other)
     }
 }
 
+fn execute_multiply_dict_and_numeric(dict: IndexMap<MapKey, ExecuteOutput>, numeric_val: Numeric) -> ExecuteOutput {
+    let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+    let numeric_val = ExecuteOutput::Numeric(numeric_val);
+    for (key, val) in dict {
+        output.insert(key, execute_multiply(val, numeric_val.clone()));
+    }
+
+    ExecuteOutput::Map(output)
+}
+
+fn execute_multiply_records_and_dict(records: Vec<ExecuteOutput>, dict: IndexMap<MapKey, ExecuteOutput>) -> ExecuteOutput {
+    let dict = ExecuteOutput::Map(dict);
+
+    ExecuteOutput::Array(records.into_iter().map(|record| execute_multiply(record, dict.clone())).collect())
+}
+
 fn execute_multiply_arrays(lhs_array: Vec<ExecuteOutput>, rhs_array: Vec<ExecuteOutput>) -> ExecuteOutput {
     if lhs_array.len() != rhs_array.len() {
         panic!("Cannot multiply arrays of different lengths {:?} vs {:?}", lhs_array.len(), rhs_array.len());
@@ -35,16 +65,11 @@ fn execute_multiply_arrays(lhs_array: Vec<ExecuteOutput>, rhs_array: Vec<Execute
     ExecuteOutput::Array(output)
 }
 
-fn execute_multiply_dicts(lhs_map: HashMap<String, ExecuteOutput>, rhs_map: HashMap<String, ExecuteOutput>) -> ExecuteOutput {
-    if lhs_map.len() != rhs_map.len() {
-        panic!("Cannot multiply dicts of different lengths: {:?} vs {:?}", lhs_map.len(), rhs_map.len());
-    }
-
-    let mut output: HashMap<String, ExecuteOutput> = HashMap::new();
+fn execute_multiply_dicts(lhs_map: IndexMap<MapKey, ExecuteOutput>, rhs_map: IndexMap<MapKey, ExecuteOutput>) -> ExecuteOutput {
+    let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
 
-    for (key, value) in lhs_map {
-        let rhs_value = rhs_map.get(&key).unwrap().clone();
-        output.insert(key, execute_multiply(value, rhs_value));
+    for (key, lhs_value, rhs_value) in align_dicts(DyadicVerb::Multiply, lhs_map, rhs_map) {
+        output.insert(key, execute_multiply(lhs_value, rhs_value));
     }
 
     ExecuteOutput::Map(output)
@@ -57,4 +82,22 @@ fn execute_multiply_array_and_numeric(int_array:  Vec<ExecuteOutput>, int_val: N
     let output = int_array.into_iter().map(|x| execute_multiply(x, int_val.clone())).collect();
 
     ExecuteOutput::Array(output)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    #[test]
+    fn multiplies_maps_by_numbers() {
+        assert_eq!(evaluate("{ 'a': 2, 'b': 1.5 } × 4").to_string(), "{'a': 8, 'b': 6.0}");
+        assert_eq!(evaluate("100 × { 'a': 0.5, 'b': null }").to_string(), "{'a': 50.0, 'b': null}");
+        assert_eq!(evaluate("2 × [{ 'a': 1 }, { 'a': 3 }]").to_string(), "[{'a': 2}, {'a': 6}]");
+    }
+
+    #[test]
+    fn multiplies_each_record_by_maps() {
+        assert_eq!(evaluate("[{ 'a': 1 }, { 'a': 3 }] × { 'a': 2 }").to_string(), "[{'a': 2}, {'a': 6}]");
+        assert_eq!(evaluate("{ 'a': 2 } × [{ 'a': 1 }, { 'a': 3 }]").to_string(), "[{'a': 2}, {'a': 6}]");
+    }
+}
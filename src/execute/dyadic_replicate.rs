@@ -15,6 +15,8 @@ fn execute_replicate_arrays(lhs_array: Vec<ExecuteOutput>, rhs_array: Vec<Execut
 
     let lhs_array: Vec<i64> = lhs_array.into_iter().map(|x| match x {
         ExecuteOutput::Numeric(Numeric::Int(x_int)) => x_int,
+        // A null in a mask, e.g. from comparing a missing value, drops the value
+        ExecuteOutput::Null => 0,
         other => panic!("Cannot replicate with {:?} values on lhs, must be array of ints as lhs", other)
     }).collect();
 
@@ -0,0 +1,128 @@
+use super::temporal::{is_temporal, apply_temporal, execute_temporal_subtract};
+use super::structures::ExecuteOutput;
+use indexmap::IndexMap;
+use super::dict_keys::align_dicts;
+use super::overflow::check_overflow;
+use crate::parse::structures::{Numeric, DyadicVerb, MapKey};
+
+pub fn execute_subtract(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    match (lhs, rhs) {
+        // Nulls propagate through the verb
+        (ExecuteOutput::Null, _) | (_, ExecuteOutput::Null) => ExecuteOutput::Null,
+        // Dates, times and durations, applied to every value of an array
+        (lhs, rhs) if is_temporal(&lhs) || is_temporal(&rhs) => apply_temporal(lhs, rhs, execute_subtract, execute_temporal_subtract),
+        // Subtracting two arrays of numbers
+        (ExecuteOutput::Array (lhs_array), ExecuteOutput::Array (rhs_array)) => execute_subtract_arrays(lhs_array, rhs_array),
+        // Subtracting a number from an array, or an array from a number
+        (ExecuteOutput::Array (int_array), ExecuteOutput::Numeric (numeric_val)) => execute_subtract_array_and_numeric(int_array, numeric_val),
+        (ExecuteOutput::Numeric (numeric_val), ExecuteOutput::Array (int_array)) => execute_subtract_numeric_and_array(numeric_val, int_array),
+        // Subtracting two numbers
+        (ExecuteOutput::Numeric (lhs_val), ExecuteOutput::Numeric (rhs_val)) => ExecuteOutput::Numeric(check_overflow(lhs_val - rhs_val)),
+        // Subtracting two dicts
+        (ExecuteOutput::Map (lhs_map), ExecuteOutput::Map (rhs_map)) => execute_subtract_dicts(lhs_map, rhs_map),
+        // Subtracting a number from a dict, or a dict from a number
+        (ExecuteOutput::Map (dict), ExecuteOutput::Numeric (numeric_val)) => execute_subtract_dict_and_numeric(dict, numeric_val),
+        (ExecuteOutput::Numeric (numeric_val), ExecuteOutput::Map (dict)) => execute_subtract_numeric_and_dict(numeric_val, dict),
+        // Subtracting a dict from each record of an array, e.g. the average record, or each record from a dict
+        (ExecuteOutput::Array (records), ExecuteOutput::Map (dict)) => execute_subtract_records_and_dict(records, dict),
+        (ExecuteOutput::Map (dict), ExecuteOutput::Array (records)) => execute_subtract_dict_and_records(dict, records),
+        (lhs_other, rhs_other) => panic!("Cannot subtract pair ({:?}, {:?})", lhs_other, rhs_other)
+    }
+}
+
+fn execute_subtract_arrays(lhs_array: Vec<ExecuteOutput>, rhs_array: Vec<ExecuteOutput>) -> ExecuteOutput {
+    if lhs_array.len() != rhs_array.len() {
+        panic!("Cannot subtract arrays of different lengths {:?} vs {:?}", lhs_array.len(), rhs_array.len());
+    }
+
+    let mut output: Vec<ExecuteOutput> = Vec::new();
+    
+    for i in 0..lhs_array.len() {
+        let lhs_val = lhs_array.get(i).unwrap().clone();
+        let rhs_val = rhs_array.get(i).unwrap().clone();
+
+        output.push(execute_subtract(lhs_val, rhs_val));
+    }
+
+    ExecuteOutput::Array(output)
+}
+
+fn execute_subtract_dicts(lhs_map: IndexMap<MapKey, ExecuteOutput>, rhs_map: IndexMap<MapKey, ExecuteOutput>) -> ExecuteOutput {
+    let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+    for (key, lhs_value, rhs_value) in align_dicts(DyadicVerb::Subtract, lhs_map, rhs_map) {
+        output.insert(key, execute_subtract(lhs_value, rhs_value));
+    }
+
+    ExecuteOutput::Map(output)
+}
+
+fn execute_subtract_array_and_numeric(int_array:  Vec<ExecuteOutput>, int_val: Numeric) -> ExecuteOutput {
+    // re-wrap numeric in an ExecuteOutput to allow being passed back into execute_subtract
+    let int_val = ExecuteOutput::Numeric(int_val);
+
+    let output = int_array.into_iter().map(|x| execute_subtract(x, int_val.clone())).collect();
+
+    ExecuteOutput::Array(output)
+}
+
+fn execute_subtract_numeric_and_array(int_val: Numeric, int_array:  Vec<ExecuteOutput>) -> ExecuteOutput {
+    // re-wrap numeric in an ExecuteOutput to allow being passed back into execute_subtract
+    let int_val = ExecuteOutput::Numeric(int_val);
+
+    let output = int_array.into_iter().map(|x| execute_subtract(int_val.clone(), x)).collect();
+
+    ExecuteOutput::Array(output)
+}
+
+fn execute_subtract_dict_and_numeric(dict: IndexMap<MapKey, ExecuteOutput>, numeric_val: Numeric) -> ExecuteOutput {
+    let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+    let numeric_val = ExecuteOutput::Numeric(numeric_val);
+    for (key, val) in dict {
+        output.insert(key, execute_subtract(val, numeric_val.clone()));
+    }
+
+    ExecuteOutput::Map(output)
+}
+
+fn execute_subtract_numeric_and_dict(numeric_val: Numeric, dict: IndexMap<MapKey, ExecuteOutput>) -> ExecuteOutput {
+    let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+    let numeric_val = ExecuteOutput::Numeric(numeric_val);
+    for (key, val) in dict {
+        output.insert(key, execute_subtract(numeric_val.clone(), val));
+    }
+
+    ExecuteOutput::Map(output)
+}
+
+fn execute_subtract_records_and_dict(records: Vec<ExecuteOutput>, dict: IndexMap<MapKey, ExecuteOutput>) -> ExecuteOutput {
+    let dict = ExecuteOutput::Map(dict);
+
+    ExecuteOutput::Array(records.into_iter().map(|record| execute_subtract(record, dict.clone())).collect())
+}
+
+fn execute_subtract_dict_and_records(dict: IndexMap<MapKey, ExecuteOutput>, records: Vec<ExecuteOutput>) -> ExecuteOutput {
+    let dict = ExecuteOutput::Map(dict);
+
+    ExecuteOutput::Array(records.into_iter().map(|record| execute_subtract(dict.clone(), record)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    #[test]
+    fn subtracts_numbers_from_maps() {
+        assert_eq!(evaluate("{ 'a': 5, 'b': 1.5 } - 1").to_string(), "{'a': 4, 'b': 0.5}");
+        assert_eq!(evaluate("10 - { 'a': 5, 'b': null }").to_string(), "{'a': 5, 'b': null}");
+        assert_eq!(evaluate("10 - [1, 4]").to_string(), "[9, 6]");
+    }
+
+    #[test]
+    fn subtracts_maps_from_each_record() {
+        assert_eq!(evaluate("[{ 'a': 5, 'b': 1 }, { 'a': 2, 'b': 4 }] - { 'a': 1, 'b': 2 }").to_string(), "[{'a': 4, 'b': -1}, {'a': 1, 'b': 2}]");
+        assert_eq!(evaluate("{ 'a': 10 } - [{ 'a': 1 }, { 'a': 4 }]").to_string(), "[{'a': 9}, {'a': 6}]");
+    }
+}
@@ -1,13 +1,45 @@
 use std::collections::HashMap;
-use crate::parse::structures::AstNode;
+use crate::parse::structures::{AstNode, DyadicVerb};
 use super::structures::ExecuteOutput;
 use super::execute_dyadic::execute_dyadic_op;
 use super::execute_monadic::execute_monadic_op;
-use super::execute_operator::execute_operator_op;
+use super::execute_operator::{execute_operator_op, execute_dyadic_operator_op};
 use super::execute_unwrap::*;
+use super::system_variables::{is_system_variable, set_system_variable, get_system_variable};
+use super::schema::check_strict_assignment;
+use super::catch::catch_error;
+use super::function::{execute_call, make_function, name_function};
+use super::modules::execute_import;
+
+pub fn execute_program(program: Vec<AstNode>, mut state: HashMap<String, ExecuteOutput>) -> HashMap<String, ExecuteOutput> {
+    // run over nodes 'line by line' executing each line
+    for line in program {
+        let node = match line {
+            AstNode::Node(inner) => {
+                *inner
+            },
+            node_matched => panic!("Unexpected node type in run program {:?}", node_matched)
+        };
+
+        // TODO: should we handle the assignment of variables out here instead? separately, then pass an immutable state in...
+        execute_expression(node, &mut state);
+    }
+
+    state
+}
 
 pub fn execute_expression(expression: AstNode, state: &mut HashMap<String, ExecuteOutput>) -> ExecuteOutput {
     match expression {
+        // The rhs is only run if the lhs raises an error, whose message is left in ⎕error
+        AstNode::DyadicOp {verb: DyadicVerb::Catch, lhs, rhs} => {
+            match catch_error(|| execute_expression(*lhs, state)) {
+                Ok (value) => value,
+                Err (message) => {
+                    set_system_variable("⎕error".to_string(), ExecuteOutput::String(message));
+                    execute_expression(*rhs, state)
+                }
+            }
+        },
         // Unwrap lhs and rhs and compute operation
         AstNode::DyadicOp {verb, lhs, rhs} => {
             let lhs = execute_expression(*lhs, state);
@@ -23,20 +55,61 @@ pub fn execute_expression(expression: AstNode, state: &mut HashMap<String, Execu
             let rhs = execute_expression(*rhs, state);
             execute_operator_op(lhs_verb, operator_verb, rhs)
         },
+        AstNode::DyadicOperatorOp {lhs_verb, operator_verb, lhs, rhs} => {
+            let lhs = execute_expression(*lhs, state);
+            let rhs = execute_expression(*rhs, state);
+            execute_dyadic_operator_op(lhs_verb, operator_verb, lhs, rhs)
+        },
         // Unwrap + compute the inner values of the array
         AstNode::Array (vals) => {
             unwrap_array(vals, state)
         },
+        // System variables are held outside of the state so verbs can read them
+        AstNode::Variable (var) if is_system_variable(&var) => {
+            get_system_variable(&var)
+        },
         // Fetch var from state and copy + return
         AstNode::Variable (var) => {
             unwrap_variable(var, state)
         },
+        AstNode::GlobalVar {variable, expression} if is_system_variable(&variable) => {
+            let value = execute_expression(*expression, state);
+            set_system_variable(variable, value);
+
+            ExecuteOutput::Null
+        },
         AstNode::Numeric (val) => {
             ExecuteOutput::Numeric(val)
         },
         AstNode::String (val) => {
             ExecuteOutput::String(val)
         },
+        AstNode::Date (val) => {
+            ExecuteOutput::Date(val)
+        },
+        AstNode::DateTime (val) => {
+            ExecuteOutput::DateTime(val)
+        },
+        AstNode::Duration (val) => {
+            ExecuteOutput::Duration(val)
+        },
+        AstNode::Null => {
+            ExecuteOutput::Null
+        },
+        AstNode::Function {source, body} => {
+            make_function(source, body, state)
+        },
+        AstNode::Call {function, args} => {
+            let function = execute_expression(*function, state);
+            let args = args.into_iter().map(|arg| execute_expression(arg, state)).collect();
+
+            execute_call(function, args)
+        },
+        AstNode::Import {path, namespace} => {
+            execute_import(&path, namespace, state);
+
+            ExecuteOutput::Null
+        },
         AstNode::Map (dict) => {
             unwrap_dictionary(dict, state)
         },
@@ -45,7 +118,11 @@ pub fn execute_expression(expression: AstNode, state: &mut HashMap<String, Execu
             //        however what if the copy_state is updated inside execute_expression
             //        we shoudl deal with that here somehow?
             let mut copy_state = state.clone();
-            state.insert(variable, execute_expression(*expression, &mut copy_state));
+            let value = name_function(execute_expression(*expression, &mut copy_state), &variable);
+
+            // Variables with a schema in ⎕strict can only be assigned matching values
+            check_strict_assignment(&variable, &value);
+            state.insert(variable, value);
 
             // TODO Merge the inner and outer states? only needed once inner executions can modify state
             ExecuteOutput::Null
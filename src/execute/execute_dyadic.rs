@@ -1,18 +1,34 @@
 use crate::parse::structures::DyadicVerb;
 use super::structures::ExecuteOutput;
 use super::dyadic_add::execute_add;
+use super::dyadic_subtract::execute_subtract;
 use super::dyadic_divide::execute_divide;
 use super::dyadic_greaterthan::execute_greaterthan;
 use super::dyadic_replicate::execute_replicate;
+use super::array_shape::execute_reshape;
 use super::dyadic_multiply::execute_multiply;
 use super::dyadic_access::execute_access;
-use super::dyadic_equals::execute_equals;
+use super::dyadic_equals::{execute_equals, execute_not_equals};
+use super::dyadic_member::execute_member;
+use super::dyadic_merge::execute_merge;
+use super::dyadic_intersect::execute_intersect;
+use super::dyadic_fill::execute_fill;
+use super::window::*;
+use super::text::*;
+use super::dyadic_group::execute_group;
+use super::schema::execute_validate;
+use super::json::execute_to_json;
+use super::file_io::execute_write_text;
+use super::csv::{execute_from_csv, execute_read_csv, execute_to_csv, execute_write_csv};
 
 pub fn execute_dyadic_op(verb: DyadicVerb, lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
     match verb {
         DyadicVerb::Add => {
             execute_add(lhs, rhs)
         },
+        DyadicVerb::Subtract => {
+            execute_subtract(lhs, rhs)
+        },
         DyadicVerb::Divide => {
             execute_divide(lhs, rhs)
         },
@@ -22,15 +38,114 @@ pub fn execute_dyadic_op(verb: DyadicVerb, lhs: ExecuteOutput, rhs: ExecuteOutpu
         DyadicVerb::Replicate => {
             execute_replicate(lhs, rhs)
         },
+        DyadicVerb::Reshape => {
+            execute_reshape(lhs, rhs)
+        },
         DyadicVerb::Equals => {
             execute_equals(lhs, rhs)
         },
+        DyadicVerb::NotEquals => {
+            execute_not_equals(lhs, rhs)
+        },
+        DyadicVerb::Member => {
+            execute_member(lhs, rhs)
+        },
         DyadicVerb::GreaterThan => {
             execute_greaterthan(lhs, rhs)
         },
         DyadicVerb::Access => {
             execute_access(lhs, rhs)
         },
-        other => panic!("Dyadic verb not implemented {:?}", other)
+        DyadicVerb::Merge => {
+            execute_merge(lhs, rhs, DyadicVerb::Right)
+        },
+        DyadicVerb::MergeWith (combine_verb) => {
+            execute_merge(lhs, rhs, *combine_verb)
+        },
+        DyadicVerb::Intersect => {
+            execute_intersect(lhs, rhs, DyadicVerb::Left)
+        },
+        DyadicVerb::IntersectWith (combine_verb) => {
+            execute_intersect(lhs, rhs, *combine_verb)
+        },
+        DyadicVerb::Left => {
+            lhs
+        },
+        DyadicVerb::Right => {
+            rhs
+        },
+        DyadicVerb::Fill => {
+            execute_fill(lhs, rhs)
+        },
+        DyadicVerb::Lag => {
+            execute_lag(lhs, rhs)
+        },
+        DyadicVerb::Lead => {
+            execute_lead(lhs, rhs)
+        },
+        DyadicVerb::MovingAverage => {
+            execute_moving_average(lhs, rhs)
+        },
+        DyadicVerb::Rank => {
+            execute_rank(Some(lhs), rhs, false)
+        },
+        DyadicVerb::DenseRank => {
+            execute_rank(Some(lhs), rhs, true)
+        },
+        DyadicVerb::CumulativeCount => {
+            execute_cumulative_count(Some(lhs), rhs)
+        },
+        DyadicVerb::Concat => {
+            execute_concat(lhs, rhs)
+        },
+        DyadicVerb::Split => {
+            execute_split(lhs, rhs)
+        },
+        DyadicVerb::Join => {
+            execute_join(lhs, rhs)
+        },
+        DyadicVerb::Substring => {
+            execute_substring(lhs, rhs)
+        },
+        DyadicVerb::Contains => {
+            execute_contains(lhs, rhs)
+        },
+        DyadicVerb::StartsWith => {
+            execute_starts_with(lhs, rhs)
+        },
+        DyadicVerb::Replace => {
+            execute_replace(lhs, rhs)
+        },
+        DyadicVerb::Group => {
+            execute_group(lhs, rhs)
+        },
+        DyadicVerb::Validate => {
+            execute_validate(lhs, rhs)
+        },
+        DyadicVerb::ToJson => {
+            execute_to_json(Some(lhs), rhs)
+        },
+        DyadicVerb::FromCsv => {
+            execute_from_csv(Some(lhs), rhs)
+        },
+        DyadicVerb::ReadCsv => {
+            execute_read_csv(Some(lhs), rhs)
+        },
+        DyadicVerb::ToCsv => {
+            execute_to_csv(Some(lhs), rhs)
+        },
+        DyadicVerb::WriteCsv => {
+            execute_write_csv(lhs, rhs)
+        },
+        DyadicVerb::WriteText => {
+            execute_write_text(lhs, rhs, false)
+        },
+        DyadicVerb::AppendText => {
+            execute_write_text(lhs, rhs, true)
+        },
+        // Catch is run lazily by execute_expression, so reaching here means the lhs didn't fail
+        DyadicVerb::Catch => {
+            lhs
+        }
     }
 }
\ No newline at end of file
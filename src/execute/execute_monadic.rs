@@ -1,37 +1,166 @@
 use super::structures::ExecuteOutput;
-use crate::parse::structures::MonadicVerb;
+use crate::parse::structures::{MonadicVerb, MapKey};
 use crate::parse::structures::Numeric;
+use super::monadic_pivot::{execute_to_columns, execute_to_records};
+use super::array_shape::{execute_shape, execute_tally, execute_transpose};
+use super::window::{execute_rank, execute_cumulative_count};
+use super::temporal::{execute_date_part, DatePart};
+use super::text::{execute_length, execute_upper, execute_lower, execute_trim};
+use super::json::{execute_from_json, execute_read_json, execute_to_json};
+use super::file_io::{execute_read_text, execute_read_lines, execute_exists, execute_list_dir};
+use super::csv::{execute_from_csv, execute_read_csv, execute_to_csv};
+use super::display::display;
+use indexmap::IndexMap;
 
 pub fn execute_monadic_op(verb: MonadicVerb, rhs: ExecuteOutput) -> ExecuteOutput {
     match verb {
         MonadicVerb::Print => {
-            println!("PRINT {:?}", rhs);
+            println!("{}", display(&rhs));
 
             ExecuteOutput::Null
         },
         MonadicVerb::Generate => {
+            // A one element shape vector counts as its length, so ⍳ ⍴ x indexes a vector
             let size = match rhs {
                 ExecuteOutput::Numeric (Numeric::Int(int_val)) => int_val,
+                ExecuteOutput::Array (arr) => match arr.as_slice() {
+                    [ExecuteOutput::Numeric (Numeric::Int(int_val))] => *int_val,
+                    _ => panic!("Cannot generate indices for shape {:?}, must be an int or a one element shape vector", arr)
+                },
                 other => panic!("Cant handle {:?} in monadic generate op", other)
             };
 
             let mut generated: Vec<ExecuteOutput> = Vec::new();
 
             for i in 0..size {
-                generated.push(ExecuteOutput::Numeric(Numeric::Int(i as i64)));
+                generated.push(ExecuteOutput::Numeric(Numeric::Int(i)));
             }
 
             ExecuteOutput::Array(generated)
         },
         MonadicVerb::Shape => {
-            // TODO: what should 'shape' of array of dicts and dicts be?
-            let expression_size = match rhs {
-                ExecuteOutput::Array (arr) => arr.len() as i64,
-                other => panic!("Cant handle {:?} in monadic shape", other)
-            };
-
-            // TODO: replace this with an outupt of int or array depending on shape of object
-            ExecuteOutput::Numeric(Numeric::Int(expression_size))
+            execute_shape(rhs)
+        },
+        MonadicVerb::Tally => {
+            execute_tally(rhs)
+        },
+        MonadicVerb::Transpose => {
+            execute_transpose(rhs)
+        },
+        MonadicVerb::IsNull => {
+            execute_isnull(rhs)
+        },
+        MonadicVerb::ToColumns => {
+            execute_to_columns(rhs)
+        },
+        MonadicVerb::ToRecords => {
+            execute_to_records(rhs)
+        },
+        MonadicVerb::Rank => {
+            execute_rank(None, rhs, false)
+        },
+        MonadicVerb::DenseRank => {
+            execute_rank(None, rhs, true)
+        },
+        MonadicVerb::CumulativeCount => {
+            execute_cumulative_count(None, rhs)
+        },
+        MonadicVerb::Year => {
+            execute_date_part(DatePart::Year, rhs)
+        },
+        MonadicVerb::Month => {
+            execute_date_part(DatePart::Month, rhs)
+        },
+        MonadicVerb::Day => {
+            execute_date_part(DatePart::Day, rhs)
+        },
+        MonadicVerb::Weekday => {
+            execute_date_part(DatePart::Weekday, rhs)
+        },
+        MonadicVerb::Length => {
+            execute_length(rhs)
+        },
+        MonadicVerb::Upper => {
+            execute_upper(rhs)
+        },
+        MonadicVerb::Lower => {
+            execute_lower(rhs)
+        },
+        MonadicVerb::Trim => {
+            execute_trim(rhs)
+        },
+        MonadicVerb::FromJson => {
+            execute_from_json(rhs)
+        },
+        MonadicVerb::ReadJson => {
+            execute_read_json(rhs)
+        },
+        MonadicVerb::ToJson => {
+            execute_to_json(None, rhs)
+        },
+        MonadicVerb::FromCsv => {
+            execute_from_csv(None, rhs)
+        },
+        MonadicVerb::ReadCsv => {
+            execute_read_csv(None, rhs)
+        },
+        MonadicVerb::ToCsv => {
+            execute_to_csv(None, rhs)
+        },
+        MonadicVerb::ReadText => {
+            execute_read_text(rhs)
+        },
+        MonadicVerb::ReadLines => {
+            execute_read_lines(rhs)
+        },
+        MonadicVerb::Exists => {
+            execute_exists(rhs)
+        },
+        MonadicVerb::ListDir => {
+            execute_list_dir(rhs)
         }
     }
-}
\ No newline at end of file
+}
+
+// Pervasive null test, giving 1 where a value is null and 0 otherwise
+fn execute_isnull(rhs: ExecuteOutput) -> ExecuteOutput {
+    match rhs {
+        ExecuteOutput::Null => ExecuteOutput::Numeric(Numeric::Int(1)),
+        ExecuteOutput::Array (arr) => ExecuteOutput::Array(arr.into_iter().map(execute_isnull).collect()),
+        ExecuteOutput::Map (dict) => {
+            let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+            for (key, value) in dict {
+                output.insert(key, execute_isnull(value));
+            }
+
+            ExecuteOutput::Map(output)
+        },
+        _ => ExecuteOutput::Numeric(Numeric::Int(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    #[test]
+    fn generates_indices_for_a_length_or_shape() {
+        assert_eq!(evaluate("⍳ 3").to_string(), "[0, 1, 2]");
+        assert_eq!(evaluate("⍳ ⍴ ['a', 'b']").to_string(), "[0, 1]");
+        assert_eq!(evaluate("⍳ 0").to_string(), "[]");
+    }
+
+    #[test]
+    #[should_panic(expected = "must be an int or a one element shape vector")]
+    fn rejects_shapes_of_many_axes() {
+        evaluate("⍳ ⍴ [[1, 2], [3, 4]]");
+    }
+
+    #[test]
+    fn tests_for_nulls_through_arrays_and_maps() {
+        assert_eq!(evaluate("isnull null").to_string(), "1");
+        assert_eq!(evaluate("isnull [1, null, [null, 'x']]").to_string(), "[0, 1, [1, 0]]");
+        assert_eq!(evaluate("isnull { 'a': null, 'b': 0 }").to_string(), "{'a': 1, 'b': 0}");
+    }
+}
@@ -1,12 +1,24 @@
 use super::structures::ExecuteOutput;
 use crate::parse::structures::{DyadicVerb, OperatorVerb};
-use super::operator_reduce::execute_reduce_dyadic_lhs;
+use super::operator_reduce::{execute_reduce_dyadic_lhs, execute_nwise_reduce};
+use super::operator_scan::execute_scan;
 
 pub fn execute_operator_op(lhs_verb: DyadicVerb, operator_verb: OperatorVerb, rhs: ExecuteOutput) -> ExecuteOutput {
     match operator_verb {
         OperatorVerb::Reduce => {
             execute_reduce_dyadic_lhs(lhs_verb, rhs)
         },
-        other => panic!("Operator verb not implemented {:?}", other)
+        OperatorVerb::Scan => {
+            execute_scan(lhs_verb, rhs)
+        }
     }
-}
\ No newline at end of file
+}
+
+pub fn execute_dyadic_operator_op(lhs_verb: DyadicVerb, operator_verb: OperatorVerb, lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    match operator_verb {
+        OperatorVerb::Reduce => {
+            execute_nwise_reduce(lhs_verb, lhs, rhs)
+        },
+        other => panic!("Operator verb {:?} cannot be used with a lhs argument", other)
+    }
+}
@@ -1,7 +1,9 @@
-use crate::parse::structures::AstNode;
+use crate::parse::structures::{AstNode, MapKey};
 use super::structures::ExecuteOutput;
 use super::execute::execute_expression;
+use super::stdlib::stdlib_value;
 use std::collections::HashMap;
+use indexmap::IndexMap;
 
 
 pub fn unwrap_array(vals: Vec<AstNode>, state: &mut HashMap<String, ExecuteOutput>) -> ExecuteOutput {
@@ -18,16 +20,31 @@ pub fn unwrap_array(vals: Vec<AstNode>, state: &mut HashMap<String, ExecuteOutpu
             },
             AstNode::Map (dict_val) => {
                 array.push(unwrap_dictionary(dict_val, state))
+            },
+            AstNode::String (string_val) => {
+                array.push(ExecuteOutput::String(string_val))
+            },
+            AstNode::Date (date_val) => {
+                array.push(ExecuteOutput::Date(date_val))
+            },
+            AstNode::DateTime (datetime_val) => {
+                array.push(ExecuteOutput::DateTime(datetime_val))
+            },
+            AstNode::Duration (duration_val) => {
+                array.push(ExecuteOutput::Duration(duration_val))
+            },
+            AstNode::Null => {
+                array.push(ExecuteOutput::Null)
             }
             other => panic!("cant handle array of: {:?}", other)
         };
     };
 
-    return ExecuteOutput::Array(array)
+    ExecuteOutput::Array(array)
 }
 
-pub fn unwrap_dictionary(dict: HashMap<String, AstNode>, state: &mut HashMap<String, ExecuteOutput>) -> ExecuteOutput {
-    let mut unwrapped_dict: HashMap<String, ExecuteOutput> = HashMap::new();
+pub fn unwrap_dictionary(dict: IndexMap<MapKey, AstNode>, state: &mut HashMap<String, ExecuteOutput>) -> ExecuteOutput {
+    let mut unwrapped_dict: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
 
     for (key, value) in dict {
         unwrapped_dict.insert(key, execute_expression(value, state));
@@ -38,15 +55,21 @@ pub fn unwrap_dictionary(dict: HashMap<String, AstNode>, state: &mut HashMap<Str
 
 // Given a variable name, unwrap its value, copy the data from state and return a new execute output
 pub fn unwrap_variable(var: String, state: &HashMap<String, ExecuteOutput>) -> ExecuteOutput {
-    let var_value = match state.get(&var).unwrap() {
+    // Fall back to the standard library for names the program hasn't defined
+    let value = match state.get(&var) {
+        Some (value) => value,
+        None => return stdlib_value(&var).unwrap_or_else(|| panic!("Variable {} is not defined", var))
+    };
+
+    match value {
         ExecuteOutput::Array (arr) => {
             ExecuteOutput::Array(arr.clone())
         },
         ExecuteOutput::Map (dict) => {
-            let mut copied_dict: HashMap<String, ExecuteOutput> = HashMap::new();
+            let mut copied_dict: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
 
             for (key, values) in dict {
-                copied_dict.insert(key.to_string(), values.clone());
+                copied_dict.insert(key.clone(), values.clone());
             }
 
             ExecuteOutput::Map(copied_dict)
@@ -54,8 +77,23 @@ pub fn unwrap_variable(var: String, state: &HashMap<String, ExecuteOutput>) -> E
         ExecuteOutput::Numeric (int_val) => {
             ExecuteOutput::Numeric(int_val.clone())
         },
-        other => panic!("Cant handle variables of type {:?}", other)
-    };
-
-    var_value
+        ExecuteOutput::String (string_val) => {
+            ExecuteOutput::String(string_val.clone())
+        },
+        ExecuteOutput::Date (date_val) => {
+            ExecuteOutput::Date(*date_val)
+        },
+        ExecuteOutput::DateTime (datetime_val) => {
+            ExecuteOutput::DateTime(*datetime_val)
+        },
+        ExecuteOutput::Duration (duration_val) => {
+            ExecuteOutput::Duration(*duration_val)
+        },
+        ExecuteOutput::Function (function_val) => {
+            ExecuteOutput::Function(function_val.clone())
+        },
+        ExecuteOutput::Null => {
+            ExecuteOutput::Null
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,118 @@
+use super::structures::ExecuteOutput;
+use crate::parse::structures::Numeric;
+use std::fs;
+use std::io::{self, Read, Write};
+
+// Paths are relative to the directory the interpreter is run from, and - reads from stdin. Failures
+// such as a missing file are IO ERRORs, which can be caught e.g. (readtext 'notes.txt') catch ''
+
+// Whole contents of a text file
+pub fn execute_read_text(rhs: ExecuteOutput) -> ExecuteOutput {
+    ExecuteOutput::String(read_file(&file_path(rhs)))
+}
+
+// Lines of a text file, without their line endings
+pub fn execute_read_lines(rhs: ExecuteOutput) -> ExecuteOutput {
+    let text = read_file(&file_path(rhs));
+
+    ExecuteOutput::Array(text.lines().map(|line| ExecuteOutput::String(line.to_string())).collect())
+}
+
+// Write the lhs text, or array of lines, to the rhs path, replacing the file or adding to its end
+pub fn execute_write_text(lhs: ExecuteOutput, rhs: ExecuteOutput, append: bool) -> ExecuteOutput {
+    write_file(&file_path(rhs), &file_text(lhs), append);
+
+    ExecuteOutput::Null
+}
+
+// 1 if anything exists at the path, 0 otherwise
+pub fn execute_exists(rhs: ExecuteOutput) -> ExecuteOutput {
+    let exists = fs::metadata(file_path(rhs)).is_ok();
+
+    ExecuteOutput::Numeric(Numeric::Int(exists as i64))
+}
+
+// Names of the entries of a directory in sorted order
+pub fn execute_list_dir(rhs: ExecuteOutput) -> ExecuteOutput {
+    let path = file_path(rhs);
+
+    let entries = match fs::read_dir(&path) {
+        Ok (entries) => entries,
+        Err (err) => panic!("IO ERROR: cannot list directory {:?}: {}", path, err)
+    };
+
+    let mut names: Vec<String> = entries.map(|entry| match entry {
+        Ok (entry) => entry.file_name().to_string_lossy().into_owned(),
+        Err (err) => panic!("IO ERROR: cannot list directory {:?}: {}", path, err)
+    }).collect();
+    names.sort();
+
+    ExecuteOutput::Array(names.into_iter().map(ExecuteOutput::String).collect())
+}
+
+pub fn file_path(val: ExecuteOutput) -> String {
+    match val {
+        ExecuteOutput::String (path) => path,
+        other => panic!("Expected a file path, found {:?}", other)
+    }
+}
+
+// The path - reads everything from stdin instead, e.g. readlines '-'
+pub fn read_file(path: &str) -> String {
+    if path == "-" {
+        let mut text = String::new();
+
+        return match io::stdin().read_to_string(&mut text) {
+            Ok (_) => text,
+            Err (err) => panic!("IO ERROR: cannot read stdin: {}", err)
+        };
+    }
+
+    match fs::read_to_string(path) {
+        Ok (text) => text,
+        Err (err) => panic!("IO ERROR: cannot read {:?}: {}", path, err)
+    }
+}
+
+pub fn write_file(path: &str, text: &str, append: bool) {
+    let file = fs::OpenOptions::new().write(true).create(true).append(append).truncate(!append).open(path);
+
+    if let Err (err) = file.and_then(|mut file| file.write_all(text.as_bytes())) {
+        panic!("IO ERROR: cannot write {:?}: {}", path, err);
+    }
+}
+
+// Text to write, where an array of lines gets a newline after each line
+fn file_text(val: ExecuteOutput) -> String {
+    match val {
+        ExecuteOutput::String (text) => text,
+        ExecuteOutput::Array (lines) => lines.into_iter().map(|line| match line {
+            ExecuteOutput::String (line) => line + "\n",
+            other => panic!("Cannot write {:?} as a line of text, must be a string", other)
+        }).collect(),
+        other => panic!("Cannot write {:?} as text, must be a string or array of lines", other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    #[test]
+    #[should_panic(expected = "IO ERROR: cannot read \"/nonexistent/notes.txt\"")]
+    fn reports_a_missing_file() {
+        evaluate("readtext '/nonexistent/notes.txt'");
+    }
+
+    #[test]
+    fn catches_a_missing_file() {
+        let message = evaluate("(readtext '/nonexistent/notes.txt') catch ⎕error").to_string();
+        assert!(message.starts_with("'IO ERROR: cannot read \"/nonexistent/notes.txt\""), "{}", message);
+    }
+
+    #[test]
+    #[should_panic(expected = "IO ERROR: cannot list directory")]
+    fn reports_a_missing_directory() {
+        evaluate("listdir '/nonexistent'");
+    }
+}
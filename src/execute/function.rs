@@ -0,0 +1,134 @@
+use super::structures::{ExecuteOutput, Function};
+use super::execute::execute_expression;
+use crate::parse::structures::AstNode;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+// A function sees the variables defined before it, so it keeps those its body refers to. Other
+// variables are left out, so defining a function doesn't copy the whole workspace. Functions
+// defined later aren't seen, so two functions can't call each other, even within one module
+pub fn make_function(source: String, body: Vec<AstNode>, state: &HashMap<String, ExecuteOutput>) -> ExecuteOutput {
+    let mut names: HashSet<&str> = HashSet::new();
+
+    for statement in &body {
+        referenced_variables(statement, &mut names);
+    }
+
+    let closure = names.into_iter()
+        .filter_map(|name| state.get(name).map(|value| (name.to_string(), value.clone())))
+        .collect();
+
+    ExecuteOutput::Function(Function { source, body: Rc::new(body), closure: Rc::new(closure), name: None })
+}
+
+// Give a function assigned to a variable that name, so it can call itself e.g. recursively
+pub fn name_function(value: ExecuteOutput, variable: &str) -> ExecuteOutput {
+    match value {
+        ExecuteOutput::Function (function) if function.name.is_none() => {
+            ExecuteOutput::Function(Function { name: Some(variable.to_string()), ..function })
+        },
+        other => other
+    }
+}
+
+// Run a function with its arguments bound to ⍵, or ⍺ and ⍵ for two, in the variables it could see
+// where it was written. Statements run in turn and the value of the last one is the result
+pub fn execute_call(function: ExecuteOutput, args: Vec<ExecuteOutput>) -> ExecuteOutput {
+    let function = match function {
+        ExecuteOutput::Function (function) => function,
+        other => panic!("Cannot call {:?}, it is not a function", other)
+    };
+
+    let mut state = (*function.closure).clone();
+    let mut args = args.into_iter();
+
+    if let Some (name) = &function.name {
+        state.insert(name.clone(), ExecuteOutput::Function(function.clone()));
+    }
+
+    match (args.next(), args.next()) {
+        (Some (rhs), None) => {
+            state.insert("⍵".to_string(), rhs);
+        },
+        (Some (lhs), Some (rhs)) => {
+            state.insert("⍺".to_string(), lhs);
+            state.insert("⍵".to_string(), rhs);
+        },
+        _ => panic!("Functions take one or two arguments")
+    }
+
+    let mut result = ExecuteOutput::Null;
+
+    for statement in function.body.iter() {
+        result = execute_expression(statement.clone(), &mut state);
+    }
+
+    result
+}
+
+fn referenced_variables<'a>(node: &'a AstNode, names: &mut HashSet<&'a str>) {
+    match node {
+        AstNode::Variable (name) => {
+            names.insert(name);
+        },
+        AstNode::Node (inner) => referenced_variables(inner, names),
+        AstNode::MonadicOp {rhs, ..} | AstNode::OperatorOp {rhs, ..} => referenced_variables(rhs, names),
+        AstNode::DyadicOp {lhs, rhs, ..} | AstNode::DyadicOperatorOp {lhs, rhs, ..} => {
+            referenced_variables(lhs, names);
+            referenced_variables(rhs, names);
+        },
+        AstNode::GlobalVar {expression, ..} => referenced_variables(expression, names),
+        AstNode::Terms (nodes) | AstNode::Array (nodes) | AstNode::Function {body: nodes, ..} => {
+            nodes.iter().for_each(|node| referenced_variables(node, names));
+        },
+        AstNode::Map (dict) => dict.values().for_each(|node| referenced_variables(node, names)),
+        AstNode::Call {function, args} => {
+            referenced_variables(function, names);
+            args.iter().for_each(|node| referenced_variables(node, names));
+        },
+        AstNode::Numeric (_) | AstNode::String (_) | AstNode::Date (_) | AstNode::DateTime (_) | AstNode::Duration (_)
+            | AstNode::Null | AstNode::Import {..} => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    #[test]
+    fn calls_with_one_or_two_arguments() {
+        assert_eq!(evaluate("sq <- { ⍵ × ⍵ }\nsq[[1, 2, 3]]").to_string(), "[1, 4, 9]");
+        assert_eq!(evaluate("scale <- { ⍺ × ⍵ }\nscale[10; 2.5]").to_string(), "25.0");
+        assert_eq!(evaluate("f <- {\n    d <- ⍵ - 1 ⋄ e <- d × 2\n    e + ⍺\n}\nf[1; 4]").to_string(), "7");
+        assert_eq!(evaluate("m <- { 'double': { 2 × ⍵ } }\nm.double[21]").to_string(), "42");
+    }
+
+    #[test]
+    fn sees_variables_defined_before_it() {
+        assert_eq!(evaluate("x <- 5\ng <- { ⍵ + x }\nx <- 7\ng[1]").to_string(), "6");
+        assert_eq!(evaluate("x <- 5\ng <- { y <- ⍵ ⋄ y + x }\ny <- 100\ng[1]").to_string(), "6");
+    }
+
+    #[test]
+    fn sees_its_own_name() {
+        assert_eq!(evaluate("f <- { f }\nf[1]").to_string(), "{ f }");
+        assert_eq!(evaluate("f <- { 1 }\nf <- { f }\nf[1]").to_string(), "{ f }");
+    }
+
+    #[test]
+    #[should_panic(expected = "Variable g is not defined")]
+    fn does_not_see_functions_defined_after_it() {
+        evaluate("f <- { g[⍵] }\ng <- { ⍵ + 1 }\nf[1]");
+    }
+
+    #[test]
+    fn defines_many_functions_holding_others() {
+        let mut source = "f0 <- { ⍵ + 1 }\n".to_string();
+
+        for i in 1..200 {
+            source += &format!("f{} <- {{ f{} ⋄ ⍵ + {} }}\n", i, i - 1, i + 1);
+        }
+
+        assert_eq!(evaluate(&(source + "f199[0] + f3[f2[1]]")).to_string(), "208");
+    }
+}
@@ -0,0 +1,300 @@
+use super::structures::ExecuteOutput;
+use crate::parse::structures::{Numeric, MapKey};
+use pest::Parser;
+use indexmap::IndexMap;
+use super::file_io::{file_path, read_file};
+
+// JSON maps onto values directly: objects are maps (keeping their key order), arrays are arrays,
+// strings are strings and null is null. As there is no bool type true and false become 1 and 0.
+// Numbers written without a fraction or exponent are ints (big ints if too large), otherwise floats.
+//
+// Going the other way, floats are always written with a fraction or exponent so they read back
+// as floats, exact decimals are written with all their digits, infinities and NaN become null,
+// and dates, date times and durations become ISO 8601 strings. JSON keys are strings, so int and
+// date keys are written as strings and composite keys as the JSON array of their parts
+#[derive(Parser)]
+#[grammar = "json_grammar.pest"]
+struct JsonParser;
+
+// Parse a JSON string into a value
+pub fn execute_from_json(rhs: ExecuteOutput) -> ExecuteOutput {
+    match rhs {
+        ExecuteOutput::String (text) => parse_json(&text),
+        // Parse every string of an array, e.g. lines of newline delimited JSON
+        ExecuteOutput::Array (arr) => ExecuteOutput::Array(arr.into_iter().map(execute_from_json).collect()),
+        ExecuteOutput::Null => ExecuteOutput::Null,
+        other => panic!("Cannot parse {:?} as JSON, must be a string", other)
+    }
+}
+
+// Read a JSON file, given its path, into a value
+pub fn execute_read_json(rhs: ExecuteOutput) -> ExecuteOutput {
+    parse_json(&read_file(&file_path(rhs)))
+}
+
+// Serialise a value to compact JSON, or pretty printed with the lhs number of spaces of indent
+pub fn execute_to_json(lhs: Option<ExecuteOutput>, rhs: ExecuteOutput) -> ExecuteOutput {
+    let indent = match lhs {
+        None => None,
+        Some (ExecuteOutput::Numeric (Numeric::Int(x))) if x >= 0 => Some(x as usize),
+        Some (other) => panic!("JSON indent must be a non-negative int, found {:?}", other)
+    };
+
+    let mut json = String::new();
+    write_json(&rhs, indent, 0, &mut json);
+
+    ExecuteOutput::String(json)
+}
+
+pub fn parse_json(text: &str) -> ExecuteOutput {
+    let mut pairs = match JsonParser::parse(Rule::json, text) {
+        Ok (pairs) => pairs,
+        Err (err) => panic!("Invalid JSON: {}", err)
+    };
+
+    json_value(pairs.next().unwrap())
+}
+
+fn json_value(pair: pest::iterators::Pair<Rule>) -> ExecuteOutput {
+    match pair.as_rule() {
+        Rule::object => {
+            let mut dict: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+            for entry in pair.into_inner() {
+                let mut entry = entry.into_inner();
+                let key = unescape_json(entry.next().unwrap().into_inner().next().unwrap().as_str());
+                let value = json_value(entry.next().unwrap());
+
+                dict.insert(MapKey::String(key), value);
+            }
+
+            ExecuteOutput::Map(dict)
+        },
+        Rule::array => ExecuteOutput::Array(pair.into_inner().map(json_value).collect()),
+        Rule::string => ExecuteOutput::String(unescape_json(pair.into_inner().next().unwrap().as_str())),
+        Rule::number => {
+            let nstr = pair.as_str();
+
+            let numeric = match nstr.contains(['.', 'e', 'E']) {
+                true => Numeric::Float(nstr.parse().unwrap()),
+                false => match nstr.parse::<i64>() {
+                    Ok (integer) => Numeric::Int(integer),
+                    Err (_) => Numeric::BigInt(nstr.parse().unwrap())
+                }
+            };
+
+            ExecuteOutput::Numeric(numeric)
+        },
+        Rule::boolean => ExecuteOutput::Numeric(Numeric::Int((pair.as_str() == "true") as i64)),
+        Rule::null => ExecuteOutput::Null,
+        unknown => panic!("Unexpected JSON {:?}", unknown)
+    }
+}
+
+fn unescape_json(escaped: &str) -> String {
+    let mut output = String::new();
+    let mut chars = escaped.chars();
+
+    while let Some (c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some ('n') => output.push('\n'),
+            Some ('t') => output.push('\t'),
+            Some ('r') => output.push('\r'),
+            Some ('b') => output.push('\u{8}'),
+            Some ('f') => output.push('\u{c}'),
+            Some ('u') => {
+                // Characters outside the basic multilingual plane are written as a surrogate pair.
+                // Unpaired surrogates and invalid escapes become the replacement character
+                let code = match read_hex_escape(&mut chars) {
+                    Some (high @ 0xD800..=0xDBFF) => {
+                        let mut lookahead = chars.clone();
+
+                        match (lookahead.next(), lookahead.next(), read_hex_escape(&mut lookahead)) {
+                            (Some ('\\'), Some ('u'), Some (low @ 0xDC00..=0xDFFF)) => {
+                                chars = lookahead;
+                                Some(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+                            },
+                            _ => None
+                        }
+                    },
+                    code => code
+                };
+
+                output.push(code.and_then(char::from_u32).unwrap_or('\u{FFFD}'));
+            },
+            Some (other) => output.push(other),
+            None => {}
+        }
+    }
+
+    output
+}
+
+fn read_hex_escape(chars: &mut std::str::Chars) -> Option<u32> {
+    let hex: String = chars.by_ref().take(4).collect();
+
+    match hex.len() == 4 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        true => u32::from_str_radix(&hex, 16).ok(),
+        false => None
+    }
+}
+
+fn write_json(val: &ExecuteOutput, indent: Option<usize>, depth: usize, out: &mut String) {
+    match val {
+        ExecuteOutput::Null => out.push_str("null"),
+        ExecuteOutput::String (text) => write_json_string(text, out),
+        ExecuteOutput::Numeric (numeric) => out.push_str(&json_number(numeric)),
+        ExecuteOutput::Date (date) => write_json_string(&format!("{:?}", date), out),
+        ExecuteOutput::DateTime (datetime) => write_json_string(&format!("{:?}", datetime), out),
+        ExecuteOutput::Duration (duration) => write_json_string(&format!("{:?}", duration), out),
+        ExecuteOutput::Function (function) => panic!("Cannot write function {:?} as JSON", function),
+        ExecuteOutput::Array (arr) => {
+            write_json_items(arr.iter(), '[', ']', indent, depth, out, |item, out| write_json(item, indent, depth + 1, out));
+        },
+        ExecuteOutput::Map (dict) => {
+            let separator = if indent.is_some() { ": " } else { ":" };
+
+            write_json_items(dict.iter(), '{', '}', indent, depth, out, |(key, value), out| {
+                write_json_string(&json_key(key), out);
+                out.push_str(separator);
+                write_json(value, indent, depth + 1, out);
+            });
+        }
+    }
+}
+
+// Write the items of an array or map between brackets, each on its own line when pretty printing
+fn write_json_items<I, F>(items: I, open: char, close: char, indent: Option<usize>, depth: usize, out: &mut String, write_item: F)
+where
+    I: ExactSizeIterator,
+    F: Fn(I::Item, &mut String)
+{
+    out.push(open);
+
+    if items.len() == 0 {
+        out.push(close);
+        return;
+    }
+
+    for (i, item) in items.enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+
+        if let Some (indent) = indent {
+            out.push('\n');
+            out.push_str(&" ".repeat(indent * (depth + 1)));
+        }
+
+        write_item(item, out);
+    }
+
+    if let Some (indent) = indent {
+        out.push('\n');
+        out.push_str(&" ".repeat(indent * depth));
+    }
+
+    out.push(close);
+}
+
+fn json_number(numeric: &Numeric) -> String {
+    match numeric {
+        Numeric::Int (x) => x.to_string(),
+        Numeric::BigInt (x) => x.to_string(),
+        Numeric::Decimal (x) => format!("{:?}", x),
+        // Debug formatting always includes a fraction or exponent, e.g. 1300.0
+        Numeric::Float (x) if x.is_finite() => format!("{:?}", x),
+        Numeric::Float (_) => "null".to_string()
+    }
+}
+
+pub fn json_key(key: &MapKey) -> String {
+    match key {
+        MapKey::String (key) => key.clone(),
+        MapKey::Int (key) => key.to_string(),
+        MapKey::Date (key) => format!("{:?}", key),
+        MapKey::Composite (keys) => {
+            let parts: Vec<String> = keys.iter().map(|key| match key {
+                MapKey::Int (key) => key.to_string(),
+                MapKey::Composite (_) => json_key(key),
+                other => {
+                    let mut part = String::new();
+                    write_json_string(&json_key(other), &mut part);
+                    part
+                }
+            }).collect();
+
+            format!("[{}]", parts.join(","))
+        }
+    }
+}
+
+fn write_json_string(text: &str, out: &mut String) {
+    out.push('"');
+
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c)
+        }
+    }
+
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execute::test_helpers::evaluate;
+
+    fn json_string(val: &ExecuteOutput) -> String {
+        match execute_to_json(None, val.clone()) {
+            ExecuteOutput::String (json) => json,
+            other => panic!("Expected JSON text, found {:?}", other)
+        }
+    }
+
+    #[test]
+    fn unescapes_strings() {
+        assert_eq!(parse_json(r#""a\"b\\c\/d\n\té""#).to_string(), evaluate(r#""a\"b\\c/d\n\té""#).to_string());
+        assert_eq!(parse_json(r#""😀""#).to_string(), "'😀'");
+    }
+
+    #[test]
+    fn replaces_unpaired_surrogates() {
+        assert_eq!(parse_json(r#""\ud800""#).to_string(), "'\u{FFFD}'");
+        assert_eq!(parse_json(r#""\ud800A""#).to_string(), "'\u{FFFD}A'");
+        assert_eq!(parse_json(r#""\ud800x""#).to_string(), "'\u{FFFD}x'");
+        assert_eq!(parse_json(r#""\udc00😀""#).to_string(), "'\u{FFFD}😀'");
+    }
+
+    #[test]
+    fn reads_numbers_by_width() {
+        assert_eq!(format!("{:?}", parse_json("[1, -7, 12345678901234567890, 1.5, 2e3, -0.25E-1]")), format!("{:?}", evaluate("[1, -7, 12345678901234567890, 1.5, 2000.0, -0.025]")));
+    }
+
+    #[test]
+    fn writes_json() {
+        assert_eq!(json_string(&evaluate("{ 'a': [1, 2.0, null], 2021: 'x\ty\"' }")), r#"{"a":[1,2.0,null],"2021":"x\ty\""}"#);
+        assert_eq!(json_string(&evaluate("[1.5, 99999999999999999999, 12.40d]")), "[1.5,99999999999999999999,12.40]");
+    }
+
+    #[test]
+    fn round_trips_values() {
+        for source in ["{ 'name': 'it''s \\u{1F600}', 'tags': ['a', 'b\\n'], 'n': null }", "[0, -12, 1300.0, 0.1, 123456789012345678901234]", "{ 'nested': { 'deep': [[], {}] } }"] {
+            let value = evaluate(source);
+
+            assert_eq!(format!("{:?}", parse_json(&json_string(&value))), format!("{:?}", value), "round trip of {}", source);
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use super::structures::ExecuteOutput;
+use crate::parse::structures::{Numeric, MapKey};
+
+// Values that can be used as map keys: strings, ints, dates, and arrays of these as composite keys
+pub fn to_map_key(val: ExecuteOutput) -> MapKey {
+    match try_map_key(val.clone()) {
+        Some (key) => key,
+        None => panic!("Cannot use {} as a map key, must be a string, int, date or array of these", val)
+    }
+}
+
+// The map key for a value, or None for values such as null and floats that can't be keys
+pub fn try_map_key(val: ExecuteOutput) -> Option<MapKey> {
+    match val {
+        ExecuteOutput::String (key) => Some(MapKey::String(key)),
+        ExecuteOutput::Numeric (Numeric::Int(key)) => Some(MapKey::Int(key)),
+        ExecuteOutput::Date (key) => Some(MapKey::Date(key)),
+        ExecuteOutput::Array (keys) => keys.into_iter().map(try_map_key).collect::<Option<Vec<MapKey>>>().map(MapKey::Composite),
+        _ => None
+    }
+}
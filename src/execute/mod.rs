@@ -1,14 +1,43 @@
 pub mod structures;
+#[allow(clippy::module_inception)]
 pub mod execute;
 pub mod execute_dyadic;
 pub mod execute_monadic;
 pub mod execute_operator;
 pub mod execute_unwrap;
 pub mod dyadic_add;
+pub mod dyadic_subtract;
 pub mod dyadic_divide;
 pub mod dyadic_multiply;
 pub mod dyadic_equals;
+pub mod dyadic_member;
 pub mod dyadic_replicate;
 pub mod dyadic_greaterthan;
 pub mod dyadic_access;
-pub mod operator_reduce;
\ No newline at end of file
+pub mod dyadic_merge;
+pub mod dyadic_intersect;
+pub mod dyadic_fill;
+pub mod dyadic_group;
+pub mod array_shape;
+pub mod dict_keys;
+pub mod map_key;
+pub mod system_variables;
+pub mod overflow;
+pub mod schema;
+pub mod operator_reduce;
+pub mod operator_scan;
+pub mod window;
+pub mod monadic_pivot;
+pub mod temporal;
+pub mod text;
+pub mod json;
+pub mod csv;
+pub mod stream;
+pub mod display;
+pub mod file_io;
+pub mod catch;
+pub mod function;
+pub mod modules;
+pub mod stdlib;
+#[cfg(test)]
+pub mod test_helpers;
\ No newline at end of file
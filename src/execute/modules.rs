@@ -0,0 +1,255 @@
+use super::structures::ExecuteOutput;
+use super::execute::execute_program;
+use super::system_variables::get_system_variable;
+use super::stdlib::stdlib_definitions;
+use crate::parse::structures::{AstNode, MapKey};
+use crate::parse::build_ast::parse_program;
+use indexmap::IndexMap;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Imports are looked for relative to the importing file first, then in each directory of ⎕path
+// and finally of the MAPLANG_PATH environment variable. Each file is parsed once and its program
+// kept for later imports, while the files being imported are tracked to catch import cycles
+thread_local! {
+    static PROGRAMS: RefCell<HashMap<PathBuf, Vec<AstNode>>> = RefCell::new(HashMap::new());
+    static FILES: RefCell<Vec<PathBuf>> = const { RefCell::new(Vec::new()) };
+}
+
+// Leaves the file being run when dropped, even if running it failed
+struct FileGuard;
+
+impl Drop for FileGuard {
+    fn drop(&mut self) {
+        FILES.with(|files| files.borrow_mut().pop());
+    }
+}
+
+// Mark the main script as the file imports are relative to
+pub fn enter_file(path: &Path) {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+    FILES.with(|files| files.borrow_mut().push(path));
+}
+
+// Run a file, or the standard library for 'std', and copy the variables it defines into the state or
+// into a map named by namespace
+pub fn execute_import(path: &str, namespace: Option<String>, state: &mut HashMap<String, ExecuteOutput>) {
+    let definitions = match path {
+        "std" => stdlib_definitions(),
+        path => execute_file(path)
+    };
+
+    match namespace {
+        None => state.extend(definitions),
+        Some (namespace) => {
+            let mut names: Vec<String> = definitions.keys().cloned().collect();
+            names.sort();
+
+            let mut module: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+            for name in names {
+                let value = definitions[&name].clone();
+                module.insert(MapKey::String(name), value);
+            }
+
+            state.insert(namespace, ExecuteOutput::Map(module));
+        }
+    }
+}
+
+// Run the file at the import path, giving the variables it defines
+fn execute_file(path: &str) -> HashMap<String, ExecuteOutput> {
+    let resolved = resolve_import(path);
+
+    let chain = FILES.with(|files| files.borrow().clone());
+
+    if chain.contains(&resolved) {
+        let cycle: Vec<String> = chain.iter().skip_while(|file| **file != resolved).chain(std::iter::once(&resolved))
+            .map(|file| file.display().to_string())
+            .collect();
+
+        panic!("IMPORT ERROR: import cycle {}", cycle.join(" -> "));
+    }
+
+    let program = load_program(&resolved);
+
+    FILES.with(|files| files.borrow_mut().push(resolved));
+    let _guard = FileGuard;
+
+    execute_program(program, HashMap::new())
+}
+
+fn resolve_import(path: &str) -> PathBuf {
+    let importing_dir = FILES.with(|files| files.borrow().last().and_then(|file| file.parent().map(Path::to_path_buf)));
+
+    let mut dirs: Vec<PathBuf> = vec![importing_dir.unwrap_or_else(|| PathBuf::from("."))];
+    dirs.extend(search_path());
+
+    for dir in &dirs {
+        let candidate = dir.join(path);
+
+        if candidate.is_file() {
+            return fs::canonicalize(&candidate).unwrap_or(candidate);
+        }
+    }
+
+    let searched: Vec<String> = dirs.iter().map(|dir| dir.display().to_string()).collect();
+    panic!("IMPORT ERROR: cannot find {:?} in {}", path, searched.join(", "));
+}
+
+fn search_path() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = match get_system_variable("⎕path") {
+        ExecuteOutput::Null => vec![],
+        ExecuteOutput::String (dir) => vec![PathBuf::from(dir)],
+        ExecuteOutput::Array (dirs) => dirs.into_iter().map(|dir| match dir {
+            ExecuteOutput::String (dir) => PathBuf::from(dir),
+            other => panic!("⎕path must hold directory names, found {:?}", other)
+        }).collect(),
+        other => panic!("⎕path must be a directory or array of directories, found {:?}", other)
+    };
+
+    if let Some (paths) = env::var_os("MAPLANG_PATH") {
+        dirs.extend(env::split_paths(&paths));
+    }
+
+    dirs
+}
+
+// Parsed program of a file, parsing it on first use
+fn load_program(path: &Path) -> Vec<AstNode> {
+    if let Some (program) = PROGRAMS.with(|programs| programs.borrow().get(path).cloned()) {
+        return program;
+    }
+
+    let source = match fs::read_to_string(path) {
+        Ok (source) => source,
+        Err (err) => panic!("IMPORT ERROR: cannot read {:?}: {}", path, err)
+    };
+
+    let program = match parse_program(&source) {
+        Ok (program) => program,
+        Err (err) => panic!("IMPORT ERROR: cannot parse {:?}: {}", path, err)
+    };
+
+    PROGRAMS.with(|programs| programs.borrow_mut().insert(path.to_path_buf(), program.clone()));
+
+    program
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execute::catch::catch_error;
+
+    // A fresh directory for a test's files, given as (relative path, source) pairs
+    fn write_files(test: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = env::temp_dir().join(format!("maplang_{}_{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        for (path, source) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, source).unwrap();
+        }
+
+        dir
+    }
+
+    // Run the program in the file as the main script, giving the variables it defines
+    fn run_file(path: &Path) -> HashMap<String, ExecuteOutput> {
+        enter_file(path);
+        let _guard = FileGuard;
+
+        execute_program(parse_program(&fs::read_to_string(path).unwrap()).unwrap(), HashMap::new())
+    }
+
+    fn value_of(state: &HashMap<String, ExecuteOutput>, name: &str) -> String {
+        state[name].to_string()
+    }
+
+    #[test]
+    fn imports_into_the_workspace_or_a_namespace() {
+        let dir = write_files("namespaces", &[
+            ("main.map", "import 'stats.map'\nimport 'stats.map' as s\na <- mean[[1, 2]]\nb <- s.double[4]\nc <- s.'pi'"),
+            ("stats.map", "pi <- 3\ndouble <- { 2 × ⍵ }\nmean <- { (+/ ⍵) ÷ ≢ ⍵ }")
+        ]);
+
+        let state = run_file(&dir.join("main.map"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!((value_of(&state, "a"), value_of(&state, "b"), value_of(&state, "c")), ("1.5".to_string(), "8".to_string(), "3".to_string()));
+        assert_eq!(value_of(&state, "pi"), "3");
+    }
+
+    // The importing file's directory comes first, then ⎕path, then MAPLANG_PATH
+    #[test]
+    fn resolves_imports_in_order() {
+        let dir = write_files("resolution", &[
+            ("main.map", "⎕path <- ['lib']\nimport 'nested/inner.map'\nimport 'both.map'\nimport 'env_only.map'"),
+            ("nested/inner.map", "import 'sibling.map'\ninner <- sibling"),
+            ("nested/sibling.map", "sibling <- 'nested'"),
+            ("lib/sibling.map", "sibling <- 'lib'"),
+            ("lib/both.map", "both <- 'lib'"),
+            ("env/both.map", "both <- 'env'"),
+            ("env/env_only.map", "env_only <- 'env'")
+        ]);
+
+        // Relative ⎕path entries are relative to the directory the interpreter runs from
+        let previous_dir = env::current_dir().unwrap();
+        env::set_current_dir(&dir).unwrap();
+        env::set_var("MAPLANG_PATH", dir.join("env"));
+
+        // Restore the environment even if an import fails
+        let mut state = HashMap::new();
+        let result = catch_error(|| {
+            state = run_file(&dir.join("main.map"));
+            ExecuteOutput::Null
+        });
+
+        env::remove_var("MAPLANG_PATH");
+        env::set_current_dir(previous_dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(result.is_ok(), "{:?}", result);
+        assert_eq!(value_of(&state, "inner"), "'nested'");
+        assert_eq!(value_of(&state, "both"), "'lib'");
+        assert_eq!(value_of(&state, "env_only"), "'env'");
+    }
+
+    #[test]
+    fn rejects_import_cycles() {
+        let dir = write_files("cycle", &[
+            ("a.map", "import 'b.map'"),
+            ("b.map", "import 'a.map'")
+        ]);
+
+        let result = catch_error(|| {
+            run_file(&dir.join("a.map"));
+            ExecuteOutput::Null
+        });
+        fs::remove_dir_all(&dir).unwrap();
+
+        let message = result.unwrap_err();
+        assert!(message.starts_with("IMPORT ERROR: import cycle"), "{}", message);
+        assert!(message.contains("b.map -> ") && message.ends_with("a.map"), "{}", message);
+    }
+
+    #[test]
+    fn parses_each_file_once() {
+        let dir = write_files("cache", &[
+            ("main.map", "import 'values.map'"),
+            ("values.map", "value <- 1")
+        ]);
+
+        let first = run_file(&dir.join("main.map"));
+        fs::write(dir.join("values.map"), "value <- 2").unwrap();
+        let second = run_file(&dir.join("main.map"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!((value_of(&first, "value"), value_of(&second, "value")), ("1".to_string(), "1".to_string()));
+    }
+}
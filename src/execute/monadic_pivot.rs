@@ -0,0 +1,105 @@
+use super::structures::ExecuteOutput;
+use crate::parse::structures::MapKey;
+use super::dyadic_access::execute_access_array_with_key;
+use super::dyadic_fill::execute_fill;
+use indexmap::IndexMap;
+
+// Pivot an array of maps (records) into a map of arrays (columns), e.g.
+// [{'a': 1, 'b': 2}, {'a': 3}] becomes {'a': [1, 3], 'b': [2, null]}
+pub fn execute_to_columns(rhs: ExecuteOutput) -> ExecuteOutput {
+    let records = match rhs {
+        ExecuteOutput::Array (arr) => arr,
+        other => panic!("Cannot pivot {:?} to columns, must be an array of maps", other)
+    };
+
+    // Every key found in any record becomes a column
+    let mut all_keys: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+    for record in &records {
+        match record {
+            ExecuteOutput::Map (dict) => {
+                for key in dict.keys() {
+                    all_keys.insert(key.clone(), ExecuteOutput::Null);
+                }
+            },
+            other => panic!("Cannot pivot {:?} to columns, must be an array of maps", other)
+        }
+    }
+
+    // Fill keys a record is missing with null so every column has one value per record
+    let records = match execute_fill(ExecuteOutput::Array(records), ExecuteOutput::Map(all_keys.clone())) {
+        ExecuteOutput::Array (arr) => arr,
+        other => panic!("Expected array of records after filling, found {:?}", other)
+    };
+
+    let mut columns: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+    for key in all_keys.into_keys() {
+        let column = execute_access_array_with_key(records.clone(), key.clone());
+        columns.insert(key, column);
+    }
+
+    ExecuteOutput::Map(columns)
+}
+
+// Pivot a map of arrays (columns) into an array of maps (records), e.g.
+// {'a': [1, 3], 'b': [2]} becomes [{'a': 1, 'b': 2}, {'a': 3, 'b': null}]
+pub fn execute_to_records(rhs: ExecuteOutput) -> ExecuteOutput {
+    let columns = match rhs {
+        ExecuteOutput::Map (dict) => dict,
+        other => panic!("Cannot pivot {:?} to records, must be a map of arrays", other)
+    };
+
+    let record_count = columns.values().map(|column| match column {
+        ExecuteOutput::Array (arr) => arr.len(),
+        _ => 1
+    }).max().unwrap_or(0);
+
+    let mut records: Vec<IndexMap<MapKey, ExecuteOutput>> = vec![IndexMap::new(); record_count];
+
+    for (key, column) in columns {
+        for (i, record) in records.iter_mut().enumerate() {
+            // Short columns are padded with null, and single values are repeated into every record
+            let value = match &column {
+                ExecuteOutput::Array (arr) => arr.get(i).cloned().unwrap_or(ExecuteOutput::Null),
+                other => other.clone()
+            };
+
+            record.insert(key.clone(), value);
+        }
+    }
+
+    ExecuteOutput::Array(records.into_iter().map(ExecuteOutput::Map).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    #[test]
+    fn pivots_records_to_columns() {
+        assert_eq!(evaluate("tocolumns [{ 'a': 1, 'b': 2 }, { 'a': 3 }]").to_string(), "{'a': [1, 3], 'b': [2, null]}");
+        assert_eq!(evaluate("tocolumns [{ 'b': 2 }, { 'a': 3, 'b': null }]").to_string(), "{'b': [2, null], 'a': [null, 3]}");
+        assert_eq!(evaluate("tocolumns []").to_string(), "{}");
+    }
+
+    #[test]
+    fn pivots_columns_to_records() {
+        assert_eq!(evaluate("torecords { 'a': [1, 3], 'b': [2] }").to_string(), "[{'a': 1, 'b': 2}, {'a': 3, 'b': null}]");
+        assert_eq!(evaluate("torecords { 'a': [1, 3], 'year': 2021 }").to_string(), "[{'a': 1, 'year': 2021}, {'a': 3, 'year': 2021}]");
+        assert_eq!(evaluate("torecords {}").to_string(), "[]");
+    }
+
+    #[test]
+    fn round_trips_records() {
+        let records = "[{'a': 1, 'b': 2}, {'a': 3, 'b': 4}]";
+
+        assert_eq!(evaluate(&format!("torecords tocolumns {}", records)).to_string(), records);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be an array of maps")]
+    fn rejects_arrays_of_other_values() {
+        evaluate("tocolumns [1, 2]");
+    }
+}
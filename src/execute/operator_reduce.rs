@@ -1,29 +1,69 @@
 use super::structures::ExecuteOutput;
-use crate::parse::structures::{Numeric, DyadicVerb};
+use crate::parse::datetime::Duration;
+use crate::parse::structures::{Numeric, DyadicVerb, MapKey};
 use super::dyadic_add::execute_add;
 use super::dyadic_multiply::execute_multiply;
-use std::collections::HashMap;
+use super::execute_dyadic::execute_dyadic_op;
+use indexmap::IndexMap;
 
 #[derive(Clone, Copy)]
-enum IdentityType {
-    ADD,
-    MULTIPLY
+pub enum IdentityType {
+    Add,
+    Multiply
+}
+
+// Verbs giving the same result however their values are grouped, so they can be folded from the
+// left one value at a time as well as from the right
+pub fn is_associative(verb: &DyadicVerb) -> bool {
+    matches!(verb, DyadicVerb::Add | DyadicVerb::Multiply | DyadicVerb::Merge | DyadicVerb::MergeWith (_))
+}
+
+// Reduce as APL does, from the right, so -/ [1, 2, 3] is 1 - (2 - 3)
+pub fn reduce_right(verb: &DyadicVerb, values: &[ExecuteOutput]) -> ExecuteOutput {
+    let (last, rest) = match values.split_last() {
+        Some (split) => split,
+        None => panic!("Cannot reduce {:?} over an empty array", verb)
+    };
+
+    rest.iter().rev().fold(last.clone(), |total, val| execute_dyadic_op(verb.clone(), val.clone(), total))
 }
 
 pub fn execute_reduce_dyadic_lhs(lhs_verb: DyadicVerb, rhs: ExecuteOutput) -> ExecuteOutput {
     match lhs_verb {
         DyadicVerb::Add => reduce_dyadic_add(rhs),
         DyadicVerb::Multiply => reduce_dyadic_multiply(rhs),
-        other => panic!("Cannot reduce over dyadic verb: {:?}", other)
+        DyadicVerb::Merge | DyadicVerb::MergeWith (_) => reduce_dyadic_merge(lhs_verb, rhs),
+        other => reduce_dyadic_other(other, rhs)
     }
 }
 
+// Reduce over each window of n consecutive values, e.g. 3 +/ x gives the rolling sums of x
+pub fn execute_nwise_reduce(lhs_verb: DyadicVerb, lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    let window_size = match lhs {
+        ExecuteOutput::Numeric (Numeric::Int(x)) if x > 0 => x as usize,
+        other => panic!("Window size of n-wise reduce must be a positive int, found {:?}", other)
+    };
+
+    let arr = match rhs {
+        ExecuteOutput::Array (arr) => arr,
+        other => panic!("Cannot n-wise reduce over {:?}", other)
+    };
+
+    let output = arr.windows(window_size)
+        .map(|window| execute_reduce_dyadic_lhs(lhs_verb.clone(), ExecuteOutput::Array(window.to_vec())))
+        .collect();
+
+    ExecuteOutput::Array(output)
+}
+
 fn reduce_dyadic_add(rhs: ExecuteOutput) -> ExecuteOutput {
     match rhs {
         ExecuteOutput::Array (arr) => {
-            let first = arr.get(0).unwrap().clone();
-
-            let mut total = initial_reduce_value(first, IdentityType::ADD);
+            // An empty sum is 0, as in APL
+            let mut total = match arr.first() {
+                Some (first) => initial_reduce_value(first.clone(), IdentityType::Add),
+                None => ExecuteOutput::Numeric(Numeric::Int(0))
+            };
 
             for val in arr {
                 total = execute_add(total, val);
@@ -38,9 +78,11 @@ fn reduce_dyadic_add(rhs: ExecuteOutput) -> ExecuteOutput {
 fn reduce_dyadic_multiply(rhs: ExecuteOutput) -> ExecuteOutput {
     match rhs {
         ExecuteOutput::Array (arr) => {
-            let first = arr.get(0).unwrap().clone();
-
-            let mut total = initial_reduce_value(first, IdentityType::MULTIPLY);
+            // An empty product is 1, as in APL
+            let mut total = match arr.first() {
+                Some (first) => initial_reduce_value(first.clone(), IdentityType::Multiply),
+                None => ExecuteOutput::Numeric(Numeric::Int(1))
+            };
 
             for val in arr {
                 total = execute_multiply(total, val);
@@ -52,18 +94,52 @@ fn reduce_dyadic_multiply(rhs: ExecuteOutput) -> ExecuteOutput {
     }
 }
 
-fn initial_reduce_value(template: ExecuteOutput, identity_type: IdentityType) -> ExecuteOutput {
+// Other verbs fold from the right as APL does. Like + and ×, - and ÷ reduce an empty array to
+// their identity on the right, while verbs without one can't reduce an empty array
+fn reduce_dyadic_other(verb: DyadicVerb, rhs: ExecuteOutput) -> ExecuteOutput {
+    match (&verb, rhs) {
+        (DyadicVerb::Subtract, ExecuteOutput::Array (arr)) if arr.is_empty() => ExecuteOutput::Numeric(Numeric::Int(0)),
+        (DyadicVerb::Divide, ExecuteOutput::Array (arr)) if arr.is_empty() => ExecuteOutput::Numeric(Numeric::Int(1)),
+        (_, ExecuteOutput::Array (arr)) if arr.is_empty() => panic!("DOMAIN ERROR: cannot reduce an empty array with {:?}, which has no identity", verb),
+        (_, ExecuteOutput::Array (arr)) => reduce_right(&verb, &arr),
+        (_, other) => panic!("Cannot reduce {:?} over {:?}", verb, other)
+    }
+}
+
+fn reduce_dyadic_merge(merge_verb: DyadicVerb, rhs: ExecuteOutput) -> ExecuteOutput {
+    match rhs {
+        ExecuteOutput::Array (arr) => {
+            // The empty map is the identity of merge, so partial records can be folded from nothing
+            let mut total = ExecuteOutput::Map(IndexMap::new());
+
+            for val in arr {
+                total = execute_dyadic_op(merge_verb.clone(), total, val);
+            }
+
+            total
+        },
+        other => panic!("Cannot reduce merge over {:?}", other)
+    }
+}
+
+pub fn initial_reduce_value(template: ExecuteOutput, identity_type: IdentityType) -> ExecuteOutput {
     let identity_val: i64 = match identity_type {
-        IdentityType::ADD => 0,
-        IdentityType::MULTIPLY => 1
+        IdentityType::Add => 0,
+        IdentityType::Multiply => 1
     };
 
     // Create initial value to start the reduce add operation with
     let initial = match template {
         ExecuteOutput::Numeric (Numeric::Float(_)) => ExecuteOutput::Numeric(Numeric::Float(identity_val as f64)),
-        ExecuteOutput::Numeric (Numeric::Int(_)) => ExecuteOutput::Numeric(Numeric::Int(identity_val)),
+        ExecuteOutput::Numeric (Numeric::Int(_)) | ExecuteOutput::Numeric (Numeric::BigInt(_)) | ExecuteOutput::Numeric (Numeric::Decimal(_)) => ExecuteOutput::Numeric(Numeric::Int(identity_val)),
         ExecuteOutput::Array (arr) => initial_reduce_value_array(arr, identity_type),
         ExecuteOutput::Map (dict) => initial_reduce_value_dict(dict, identity_type),
+        ExecuteOutput::Null => ExecuteOutput::Null,
+        // Durations sum from no time, but scale from a plain number
+        ExecuteOutput::Duration (_) => match identity_type {
+            IdentityType::Add => ExecuteOutput::Duration(Duration::from_seconds(0)),
+            IdentityType::Multiply => ExecuteOutput::Numeric(Numeric::Int(identity_val))
+        },
         other => panic!("Cannot handle dyadic reduce over array of {:?}", other)
     };
 
@@ -80,12 +156,42 @@ fn initial_reduce_value_array(template: Vec<ExecuteOutput>, identity_type: Ident
     ExecuteOutput::Array(initial)
 }
 
-fn initial_reduce_value_dict(template: HashMap<String, ExecuteOutput>, identity_type: IdentityType) -> ExecuteOutput {
-    let mut initial: HashMap<String, ExecuteOutput> = HashMap::new();
+fn initial_reduce_value_dict(template: IndexMap<MapKey, ExecuteOutput>, identity_type: IdentityType) -> ExecuteOutput {
+    let mut initial: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
 
     for (key, val) in template {
         initial.insert(key, initial_reduce_value(val, identity_type));
     }
 
     ExecuteOutput::Map(initial)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    #[test]
+    fn reduces_associative_verbs() {
+        assert_eq!(evaluate("+/ [1, 2.5, 3]").to_string(), "6.5");
+        assert_eq!(evaluate("×/ [2, 3, 4]").to_string(), "24");
+        assert_eq!(evaluate("+/ []").to_string(), "0");
+        assert_eq!(evaluate("×/ []").to_string(), "1");
+    }
+
+    #[test]
+    fn reduces_other_verbs_from_the_right() {
+        assert_eq!(evaluate("-/ [1, 2, 3, 4]").to_string(), "-2");
+        assert_eq!(evaluate("÷/ [8, 4, 2]").to_string(), "4");
+        assert_eq!(evaluate("=/ [0, 2, 1]").to_string(), "1");
+        assert_eq!(evaluate("-/ [{ 'a': 5 }, { 'a': 2, 'b': 1 }]").to_string(), "{'a': 3, 'b': null}");
+        assert_eq!(evaluate("-/ []").to_string(), "0");
+        assert_eq!(evaluate("÷/ []").to_string(), "1");
+        assert_eq!(evaluate("3 -/ [1, 2, 3, 4]").to_string(), "[2, 3]");
+    }
+
+    #[test]
+    #[should_panic(expected = "DOMAIN ERROR: cannot reduce an empty array with Equals")]
+    fn rejects_empty_arrays_without_an_identity() {
+        evaluate("=/ []");
+    }
+}
@@ -0,0 +1,51 @@
+use super::structures::ExecuteOutput;
+use super::execute_dyadic::execute_dyadic_op;
+use super::operator_reduce::{is_associative, reduce_right};
+use crate::parse::structures::DyadicVerb;
+
+// Running reduction, e.g. +\ [1, 2, 3] gives the cumulative sums [1, 3, 6]. As in APL each value
+// is the reduce of the values up to it, so -\ [1, 2, 3] gives [1, -1, 2]. Associative verbs fold
+// the running value along, while others reduce each prefix again from the right
+pub fn execute_scan(lhs_verb: DyadicVerb, rhs: ExecuteOutput) -> ExecuteOutput {
+    match rhs {
+        ExecuteOutput::Array (arr) if is_associative(&lhs_verb) => {
+            let mut output: Vec<ExecuteOutput> = Vec::new();
+
+            for val in arr {
+                let next = match output.last() {
+                    Some (total) => execute_dyadic_op(lhs_verb.clone(), total.clone(), val),
+                    None => val
+                };
+
+                output.push(next);
+            }
+
+            ExecuteOutput::Array(output)
+        },
+        ExecuteOutput::Array (arr) => {
+            let output = (1..=arr.len()).map(|end| reduce_right(&lhs_verb, &arr[..end])).collect();
+
+            ExecuteOutput::Array(output)
+        },
+        other => panic!("Cannot scan {:?} over {:?}", lhs_verb, other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    #[test]
+    fn scans_associative_verbs_as_running_totals() {
+        assert_eq!(evaluate("+\\ [1, 2, 3, 4]").to_string(), "[1, 3, 6, 10]");
+        assert_eq!(evaluate("×\\ [1, 2, 3, 4]").to_string(), "[1, 2, 6, 24]");
+        assert_eq!(evaluate("∪\\ [{ 'a': 1 }, { 'b': 2 }]").to_string(), "[{'a': 1}, {'a': 1, 'b': 2}]");
+        assert_eq!(evaluate("+\\ []").to_string(), "[]");
+    }
+
+    #[test]
+    fn reduces_each_prefix_from_the_right() {
+        assert_eq!(evaluate("-\\ [1, 2, 3, 4]").to_string(), "[1, -1, 2, -2]");
+        assert_eq!(evaluate("÷\\ [8, 4, 2]").to_string(), "[8, 2, 4]");
+    }
+}
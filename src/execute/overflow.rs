@@ -0,0 +1,14 @@
+use super::structures::ExecuteOutput;
+use super::system_variables::get_system_variable;
+use crate::parse::structures::Numeric;
+
+// Ints that overflow 64 bits are promoted to big ints, unless ⎕overflow is set to 'error' in
+// which case any big int result is a domain error
+pub fn check_overflow(result: Numeric) -> Numeric {
+    match (&result, get_system_variable("⎕overflow")) {
+        (Numeric::BigInt (x), ExecuteOutput::String (mode)) if mode == "error" => {
+            panic!("DOMAIN ERROR: int result {} doesn't fit in 64 bits and ⎕overflow is 'error'", x)
+        },
+        _ => result
+    }
+}
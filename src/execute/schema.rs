@@ -0,0 +1,160 @@
+use super::structures::ExecuteOutput;
+use super::system_variables::get_system_variable;
+use crate::parse::structures::{Numeric, MapKey};
+use indexmap::IndexMap;
+
+// A schema describes the shape of a value using the same literals as the data:
+// - a type name string: 'string', 'int', 'float', 'decimal', 'number', 'date', 'datetime',
+//   'duration', 'map', 'array' or 'any'
+// - a map of keys to schemas for records, where a key ending in ? is optional (missing or null).
+//   Required keys must be present and not null, and keys not in the schema are allowed
+// - an array holding one schema which every value of an array must match
+// e.g. { 'name': 'string', 'age': 'int', 'email?': 'string', 'tags': ['string'] }
+
+// Check values against a schema, giving a report of { 'valid': 1 or 0, 'errors': [...] } rather
+// than failing. An array of records checked against a record schema gives a report per record,
+// so (reports.'valid') / records keeps the valid ones
+pub fn execute_validate(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    match (lhs, &rhs) {
+        (ExecuteOutput::Array (records), ExecuteOutput::Map (_)) => {
+            ExecuteOutput::Array(records.iter().map(|record| validation_report(schema_errors(record, &rhs, ""))).collect())
+        },
+        (value, _) => validation_report(schema_errors(&value, &rhs, ""))
+    }
+}
+
+// Variables named in the ⎕strict map must match their schema whenever they are assigned,
+// e.g. ⎕strict <- { 'employees': [employee_schema] }
+pub fn check_strict_assignment(variable: &str, value: &ExecuteOutput) {
+    let schemas = match get_system_variable("⎕strict") {
+        ExecuteOutput::Map (schemas) => schemas,
+        ExecuteOutput::Null => return,
+        other => panic!("⎕strict must be a map of variable names to schemas, found {}", other)
+    };
+
+    let schema = match schemas.get(&MapKey::from(variable)) {
+        Some (schema) => schema,
+        None => return
+    };
+
+    let errors = schema_errors(value, schema, "");
+
+    if !errors.is_empty() {
+        panic!("SCHEMA ERROR: cannot assign {} as it doesn't match its schema: {}", variable, errors.join(", "));
+    }
+}
+
+fn validation_report(errors: Vec<String>) -> ExecuteOutput {
+    let mut report: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+    report.insert(MapKey::from("valid"), ExecuteOutput::Numeric(Numeric::Int(errors.is_empty() as i64)));
+    report.insert(MapKey::from("errors"), ExecuteOutput::Array(errors.into_iter().map(ExecuteOutput::String).collect()));
+
+    ExecuteOutput::Map(report)
+}
+
+// Describe every way the value doesn't match the schema, each prefixed with the path to the
+// offending value and the value written as MapLang e.g. address.city: expected string, found 1
+fn schema_errors(value: &ExecuteOutput, schema: &ExecuteOutput, path: &str) -> Vec<String> {
+    match schema {
+        ExecuteOutput::String (type_name) => match matches_type(value, type_name) {
+            true => vec![],
+            false => vec![format!("{}expected {}, found {}", path_prefix(path), type_name, value)]
+        },
+        ExecuteOutput::Map (fields) => match value {
+            ExecuteOutput::Map (record) => record_errors(record, fields, path),
+            other => vec![format!("{}expected map, found {}", path_prefix(path), other)]
+        },
+        ExecuteOutput::Array (item_schemas) => {
+            let item_schema = match item_schemas.as_slice() {
+                [item_schema] => item_schema,
+                _ => panic!("Array schemas must hold exactly one schema for the values, found {}", schema)
+            };
+
+            match value {
+                ExecuteOutput::Array (items) => items.iter().enumerate()
+                    .flat_map(|(i, item)| schema_errors(item, item_schema, &join_path(path, &i.to_string())))
+                    .collect(),
+                other => vec![format!("{}expected array, found {}", path_prefix(path), other)]
+            }
+        },
+        other => panic!("Invalid schema {}, must be a type name, map or array", other)
+    }
+}
+
+fn record_errors(record: &IndexMap<MapKey, ExecuteOutput>, fields: &IndexMap<MapKey, ExecuteOutput>, path: &str) -> Vec<String> {
+    let mut errors: Vec<String> = Vec::new();
+
+    for (field, field_schema) in fields {
+        let (key, optional) = match field {
+            MapKey::String (name) if name.ends_with('?') => (MapKey::from(name.trim_end_matches('?')), true),
+            other => (other.clone(), false)
+        };
+
+        let field_name = match &key {
+            MapKey::String (name) => name.clone(),
+            other => other.to_string()
+        };
+        let field_path = join_path(path, &field_name);
+
+        match record.get(&key) {
+            None | Some (ExecuteOutput::Null) if optional => {},
+            None => errors.push(format!("{}: missing", field_path)),
+            Some (ExecuteOutput::Null) => errors.push(format!("{}: is null", field_path)),
+            Some (field_value) => errors.extend(schema_errors(field_value, field_schema, &field_path))
+        }
+    }
+
+    errors
+}
+
+fn matches_type(value: &ExecuteOutput, type_name: &str) -> bool {
+    match (type_name, value) {
+        ("any", _) => true,
+        ("string", ExecuteOutput::String (_)) => true,
+        ("int", ExecuteOutput::Numeric (Numeric::Int(_) | Numeric::BigInt(_))) => true,
+        ("float", ExecuteOutput::Numeric (Numeric::Float(_))) => true,
+        ("decimal", ExecuteOutput::Numeric (Numeric::Decimal(_))) => true,
+        ("number", ExecuteOutput::Numeric (_)) => true,
+        ("date", ExecuteOutput::Date (_)) => true,
+        ("datetime", ExecuteOutput::DateTime (_)) => true,
+        ("duration", ExecuteOutput::Duration (_)) => true,
+        ("map", ExecuteOutput::Map (_)) => true,
+        ("array", ExecuteOutput::Array (_)) => true,
+        ("string" | "int" | "float" | "decimal" | "number" | "date" | "datetime" | "duration" | "map" | "array", _) => false,
+        (unknown, _) => panic!("Unknown schema type {:?}", unknown)
+    }
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    match path {
+        "" => key.to_string(),
+        _ => format!("{}.{}", path, key)
+    }
+}
+
+fn path_prefix(path: &str) -> String {
+    match path {
+        "" => String::new(),
+        _ => format!("{}: ", path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    #[test]
+    fn reports_errors_in_schema_order() {
+        let source = "{ 'name': 1, 'address': { 'city': [2] } } validate { 'name': 'string', 'age': 'int', 'email?': 'string', 'address': { 'city': 'string' } }";
+
+        assert_eq!(evaluate(source).to_string(), "{'valid': 0, 'errors': ['name: expected string, found 1', 'age: missing', 'address.city: expected string, found [2]']}");
+    }
+
+    #[test]
+    fn reports_each_record() {
+        let source = "([{ 'tags': ['a'] }, { 'tags': ['b', 2.5] }, { 'tags': null }] validate { 'tags': ['string'] })";
+
+        assert_eq!(evaluate(&(source.to_string() + ".'errors'")).to_string(), "[[], ['tags.1: expected string, found 2.5'], ['tags: is null']]");
+    }
+}
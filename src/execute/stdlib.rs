@@ -0,0 +1,98 @@
+use super::structures::ExecuteOutput;
+use super::execute::execute_program;
+use crate::parse::build_ast::parse_program;
+use std::collections::HashMap;
+
+// The standard library is MapLang source built into the interpreter. Variables not defined by a
+// program are looked up here, so programs can use e.g. mean[x] without importing it, and can
+// define their own mean in its place
+const STDLIB_SOURCE: &str = include_str!("stdlib.map");
+
+thread_local! {
+    static STDLIB: HashMap<String, ExecuteOutput> = {
+        let program = parse_program(STDLIB_SOURCE).unwrap_or_else(|err| panic!("Cannot parse the standard library: {}", err));
+
+        execute_program(program, HashMap::new())
+    };
+}
+
+pub fn stdlib_value(name: &str) -> Option<ExecuteOutput> {
+    STDLIB.with(|stdlib| stdlib.get(name).cloned())
+}
+
+// Everything the standard library defines, for import 'std'
+pub fn stdlib_definitions() -> HashMap<String, ExecuteOutput> {
+    STDLIB.with(|stdlib| stdlib.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers;
+
+    // Value of the last line of MapLang source, written as MapLang
+    fn evaluate(source: &str) -> String {
+        test_helpers::evaluate(source).to_string()
+    }
+
+    const EMPLOYEES: &str = "employees <- [ { 'age': 22, 'salary': 300 }, { 'age': 35, 'salary': 300 }, { 'age': 29, 'salary': 250 }, { 'age': 34, 'salary': 350 } ]\n";
+
+    #[test]
+    fn sums_counts_and_averages_numbers() {
+        assert_eq!(evaluate("sum[[3, 1, 4, 1, 5]]"), "14");
+        assert_eq!(evaluate("count[[3, 1, 4, 1, 5]]"), "5");
+        assert_eq!(evaluate("mean[[1, 2, 3, 4]]"), "2.5");
+        assert_eq!(evaluate("variance[[1, 2, 3, 4]]"), "1.25");
+        assert_eq!(evaluate("variance[[7, 7, 7]]"), "0");
+    }
+
+    #[test]
+    fn finds_medians_minimums_and_maximums() {
+        assert_eq!(evaluate("median[[3, 1, 4, 1, 5, 9, 2, 6]]"), "3.5");
+        assert_eq!(evaluate("median[[5, 1, 3]]"), "3");
+        assert_eq!(evaluate("median[[1, 1, 2, 3]]"), "1.5");
+        assert_eq!(evaluate("median[[2, 2, 2, 2]]"), "2");
+        assert_eq!(evaluate("median[[5, null, 1, 3]]"), "3");
+        assert_eq!(evaluate("median[[0.5, -2.5]]"), "-1.0");
+        assert_eq!(evaluate("minimum[[3, null, -1, 4, -1]]"), "-1");
+        assert_eq!(evaluate("maximum[[3, 9, 4, 9]]"), "9");
+    }
+
+    #[test]
+    fn gives_zero_for_empty_input() {
+        for function in ["sum", "mean", "variance", "median", "minimum", "maximum"] {
+            assert_eq!(evaluate(&format!("{}[[]]", function)), "0", "{}", function);
+        }
+
+        assert_eq!(evaluate("count[[]]"), "0");
+        assert_eq!(evaluate("median[[null, null]]"), "0");
+        assert_eq!(evaluate("percent_of_total[[]]"), "[]");
+        assert_eq!(evaluate("where[{ ⍵ > 3 }; []]"), "[]");
+    }
+
+    #[test]
+    fn filters_and_counts_with_predicates() {
+        assert_eq!(evaluate("where[{ ⍵ > 3 }; [3, 1, 4, 1, 5]]"), "[4, 5]");
+        assert_eq!(evaluate("count_where[{ ⍵ > 3 }; [3, 1, 4, 1, 5]]"), "2");
+        assert_eq!(evaluate("percent_of_total[[1, 3]]"), "[25.0, 75.0]");
+    }
+
+    #[test]
+    fn works_on_arrays_of_maps() {
+        assert_eq!(evaluate(&(EMPLOYEES.to_string() + "mean[employees]")), "{'age': 30, 'salary': 300}");
+        assert_eq!(evaluate(&(EMPLOYEES.to_string() + "variance[employees]")), "{'age': 26.5, 'salary': 1250}");
+        assert_eq!(evaluate(&(EMPLOYEES.to_string() + "sum[employees]")), "{'age': 120, 'salary': 1200}");
+        assert_eq!(evaluate(&(EMPLOYEES.to_string() + "median[employees.'age']")), "31.5");
+        assert_eq!(evaluate(&(EMPLOYEES.to_string() + "minimum[employees.'salary']")), "250");
+        assert_eq!(evaluate(&(EMPLOYEES.to_string() + "maximum[employees.'age']")), "35");
+        assert_eq!(evaluate(&(EMPLOYEES.to_string() + "count_where[{ (⍵.'salary') = 300 }; employees]")), "2");
+        assert_eq!(evaluate(&(EMPLOYEES.to_string() + "(where[{ (⍵.'age') > 30 }; employees]).'salary'")), "[300, 350]");
+        assert_eq!(evaluate("percent_of_total[[{ 'sales': 1, 'refunds': 2 }, { 'sales': 3, 'refunds': 2 }]]"), "[{'sales': 25.0, 'refunds': 50.0}, {'sales': 75.0, 'refunds': 50.0}]");
+    }
+
+    #[test]
+    fn programs_can_replace_or_import_it() {
+        assert_eq!(evaluate("mean <- { 0 }\nmean[[1, 2]]"), "0");
+        assert_eq!(evaluate("mean <- { 0 }\nimport 'std' as std\nstd.mean[[1, 2]]"), "1.5");
+        assert_eq!(evaluate("import 'std'\nmedian[[4, 8]]"), "6");
+    }
+}
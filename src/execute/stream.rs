@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use crate::parse::structures::{AstNode, DyadicVerb, OperatorVerb, Numeric};
+use super::structures::ExecuteOutput;
+use super::execute::execute_expression;
+use super::execute_dyadic::execute_dyadic_op;
+use super::operator_reduce::{execute_reduce_dyadic_lhs, is_associative};
+use super::json::{parse_json, execute_to_json};
+
+// Streaming mode applies an expression to each record of newline delimited JSON in turn, with the
+// record bound to ⍵, so input far larger than memory can be processed one line at a time. Each
+// result is written out as a line of JSON, except:
+// - a replicate at the top of the expression is a filter, e.g. ((⍵.'status') = 500) / ⍵ only writes
+//   the records where the mask is 1 (a count of n writes the record n times, and null counts as 0)
+// - a reduce at the top folds over the results of every record, writing a single line at the end,
+//   e.g. +/ ⍵.'bytes' or +/ ((⍵.'status') = 500) / ⍵.'bytes'
+// - a scan at the top writes the running value after each record, e.g. +\ ⍵.'bytes'
+//
+// Every record runs in the same workspace, with ⍵ rebound to each record in turn. Records are folded
+// in as they arrive, so reduce and scan only stream with associative verbs such as + and ×
+pub fn stream_ndjson(expression: AstNode, mut state: HashMap<String, ExecuteOutput>, input: impl BufRead, output: &mut impl Write) {
+    let mut fold: Option<ExecuteOutput> = None;
+
+    if let AstNode::OperatorOp {lhs_verb, operator_verb, ..} = &expression {
+        if !is_associative(lhs_verb) {
+            panic!("Cannot stream {:?} with {:?}, as it folds from the right. Only +, × and ∪ can be streamed", operator_verb, lhs_verb);
+        }
+    }
+
+    for (line_number, line) in input.lines().enumerate() {
+        let line = match line {
+            Ok (line) => line,
+            Err (err) => panic!("Cannot read NDJSON line {}: {}", line_number + 1, err)
+        };
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        state.insert("⍵".to_string(), parse_json(&line));
+
+        match &expression {
+            AstNode::OperatorOp {lhs_verb, operator_verb, rhs} => {
+                for value in stream_values(rhs, &mut state) {
+                    let total = fold_value(lhs_verb, fold.take(), value);
+
+                    if *operator_verb == OperatorVerb::Scan {
+                        write_ndjson(&total, output);
+                    }
+
+                    fold = Some(total);
+                }
+            },
+            other => {
+                for value in stream_values(other, &mut state) {
+                    write_ndjson(&value, output);
+                }
+            }
+        }
+    }
+
+    if let AstNode::OperatorOp {operator_verb: OperatorVerb::Reduce, ..} = &expression {
+        write_ndjson(&fold.unwrap_or(ExecuteOutput::Null), output);
+    }
+}
+
+// Values an expression gives for a single record, where filters give none for records they drop
+fn stream_values(expression: &AstNode, state: &mut HashMap<String, ExecuteOutput>) -> Vec<ExecuteOutput> {
+    match expression {
+        AstNode::DyadicOp {verb: DyadicVerb::Replicate, lhs, rhs} => {
+            let copies = match execute_expression(*lhs.clone(), state) {
+                // A mask array filters within the record as replicate usually does
+                mask @ ExecuteOutput::Array (_) => {
+                    let rhs = execute_expression(*rhs.clone(), state);
+                    return vec![execute_dyadic_op(DyadicVerb::Replicate, mask, rhs)];
+                },
+                ExecuteOutput::Numeric (Numeric::Int(x)) if x >= 0 => x as usize,
+                ExecuteOutput::Null => 0,
+                other => panic!("Streaming filter mask must be a non-negative int or null for each record, found {:?}", other)
+            };
+
+            match copies {
+                0 => vec![],
+                _ => stream_values(rhs, state).into_iter().flat_map(|value| vec![value; copies]).collect()
+            }
+        },
+        other => vec![execute_expression(other.clone(), state)]
+    }
+}
+
+// Fold one more value into a streaming reduction, combining the pair as the reduce verb would
+fn fold_value(verb: &DyadicVerb, total: Option<ExecuteOutput>, value: ExecuteOutput) -> ExecuteOutput {
+    match total {
+        None => execute_reduce_dyadic_lhs(verb.clone(), ExecuteOutput::Array(vec![value])),
+        Some (total) => execute_reduce_dyadic_lhs(verb.clone(), ExecuteOutput::Array(vec![total, value]))
+    }
+}
+
+fn write_ndjson(value: &ExecuteOutput, output: &mut impl Write) {
+    let json = match execute_to_json(None, value.clone()) {
+        ExecuteOutput::String (json) => json,
+        other => panic!("Expected JSON string, found {:?}", other)
+    };
+
+    if let Err (err) = writeln!(output, "{}", json) {
+        panic!("Cannot write NDJSON output: {}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execute::execute::execute_program;
+    use crate::parse::build_ast::parse_program;
+
+    const LOGS: &str = "{\"status\": 200, \"bytes\": 10}\n\n{\"status\": 500, \"bytes\": 5}\n{\"status\": 500, \"bytes\": 7}\n";
+
+    // Stream the logs through the last line of the source, after running the lines before it
+    fn stream(source: &str) -> String {
+        let mut program = parse_program(source).unwrap();
+
+        let expression = match program.pop() {
+            Some (AstNode::Node (inner)) => *inner,
+            other => panic!("Expected an expression to stream, found {:?}", other)
+        };
+
+        let mut output: Vec<u8> = Vec::new();
+        stream_ndjson(expression, execute_program(program, HashMap::new()), LOGS.as_bytes(), &mut output);
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn maps_and_filters_records() {
+        assert_eq!(stream("⍵.'bytes'"), "10\n5\n7\n");
+        assert_eq!(stream("((⍵.'status') = 500) / ⍵"), "{\"status\":500,\"bytes\":5}\n{\"status\":500,\"bytes\":7}\n");
+    }
+
+    #[test]
+    fn reduces_and_scans_records() {
+        assert_eq!(stream("+/ ((⍵.'status') = 500) / ⍵.'bytes'"), "12\n");
+        assert_eq!(stream("+\\ ⍵.'bytes'"), "10\n15\n22\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot stream Scan with Subtract")]
+    fn rejects_folds_from_the_right() {
+        stream("-\\ ⍵.'bytes'");
+    }
+
+    #[test]
+    fn uses_the_prelude() {
+        assert_eq!(stream("kb <- { ⍵ ÷ 1000 }\nkb[⍵.'bytes']"), "0.01\n0.005\n0.007\n");
+    }
+}
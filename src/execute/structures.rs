@@ -1,15 +1,139 @@
+use indexmap::IndexMap;
+use crate::parse::structures::{AstNode, Numeric, MapKey, string_literal};
+use crate::parse::datetime::{Date, DateTime, Duration};
 use std::collections::HashMap;
-use crate::parse::structures::Numeric;
+use std::rc::Rc;
+use std::fmt;
 
 #[derive(Debug, Clone)]
 pub enum ExecuteOutput {
     // Array of any value
     Array(Vec<ExecuteOutput>),
-    // Map of string -> any value
-    Map(HashMap<String, ExecuteOutput>), 
+    // Map of string, int, date or composite key -> any value
+    Map(IndexMap<MapKey, ExecuteOutput>), 
     // Numeric int or float
     Numeric(Numeric),
     // General string
     String(String),
+    // Calendar date, date and time of day, or length of time
+    Date(Date),
+    DateTime(DateTime),
+    Duration(Duration),
+    // Function with the variables defined where it was written
+    Function(Function),
     Null
-}
\ No newline at end of file
+}
+
+// The body and closure are shared between copies, so functions are cheap to pass around. The name
+// is the variable the function was first assigned to, which it can call itself by
+#[derive(Clone)]
+pub struct Function {
+    pub source: String,
+    pub body: Rc<Vec<AstNode>>,
+    pub closure: Rc<HashMap<String, ExecuteOutput>>,
+    pub name: Option<String>
+}
+
+// Only the source is shown, as the closure can hold much of the workspace
+impl fmt::Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+// Written as MapLang source which reads back as the same value e.g. {'age': 23.5, 'tags': ['a']}
+impl fmt::Display for ExecuteOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExecuteOutput::Array (arr) => {
+                let items: Vec<String> = arr.iter().map(|x| x.to_string()).collect();
+
+                write!(f, "[{}]", items.join(", "))
+            },
+            ExecuteOutput::Map (dict) => {
+                let entries: Vec<String> = dict.iter().map(|(key, value)| format!("{}: {}", key, value)).collect();
+
+                write!(f, "{{{}}}", entries.join(", "))
+            },
+            ExecuteOutput::Numeric (x) => write!(f, "{}", x),
+            ExecuteOutput::String (x) => write!(f, "{}", string_literal(x)),
+            ExecuteOutput::Date (x) => write!(f, "@{:?}", x),
+            ExecuteOutput::DateTime (x) => write!(f, "@{:?}", x),
+            ExecuteOutput::Duration (x) => write!(f, "@{:?}", x),
+            ExecuteOutput::Function (x) => write!(f, "{}", x.source),
+            ExecuteOutput::Null => write!(f, "null")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execute::test_helpers::evaluate;
+    use crate::parse::decimal::Decimal;
+    use num_bigint::BigInt;
+
+    // Printing a value and parsing it again must give back the same value. Debug output shows
+    // each variant and its contents, so comparing it also catches e.g. a float read back as an int
+    fn assert_round_trip(value: ExecuteOutput) {
+        let source = value.to_string();
+
+        assert_eq!(format!("{:?}", evaluate(&source)), format!("{:?}", value), "round trip of {}", source);
+    }
+
+    fn assert_source_round_trip(source: &str) {
+        assert_round_trip(evaluate(source));
+    }
+
+    #[test]
+    fn displays_as_source() {
+        assert_eq!(evaluate("{ 'age': 23.33, 'salary': 1300.0 }").to_string(), "{'age': 23.33, 'salary': 1300.0}");
+        assert_eq!(evaluate("[1, -2, 'it''s', null]").to_string(), "[1, -2, 'it''s', null]");
+    }
+
+    #[test]
+    fn round_trips_numbers() {
+        for source in ["0", "-7", "1300", "1300.0", "0.1", "-2.5", "12.40d", "-0.05d", "99999999999999999999999"] {
+            assert_source_round_trip(source);
+        }
+
+        for float in [1e300, 2.5e-7, -123456.789, f64::MAX, f64::MIN_POSITIVE] {
+            assert_round_trip(ExecuteOutput::Numeric(Numeric::Float(float)));
+        }
+
+        assert_round_trip(ExecuteOutput::Numeric(Numeric::BigInt("-123456789012345678901234567890".parse::<BigInt>().unwrap())));
+        assert_round_trip(ExecuteOutput::Numeric(Numeric::Decimal(Decimal::parse("1300.00"))));
+    }
+
+    #[test]
+    fn round_trips_strings() {
+        for text in ["", "plain", "it's", "back\\slash", "line\nbreak\ttab\r", "nul\0", "caf\u{e9} \u{1F600}", "bell\u{7}", "\"double\""] {
+            assert_round_trip(ExecuteOutput::String(text.to_string()));
+        }
+    }
+
+    #[test]
+    fn round_trips_dates_and_times() {
+        for source in ["@2021-03-04", "@1969-12-31T23:59:59", "@P3D", "@PT1H30M", "@P1DT2H3M4S", "@PT0S"] {
+            assert_source_round_trip(source);
+        }
+
+        assert_source_round_trip("@2021-03-04 - @2021-03-10");
+        assert_source_round_trip("@2021-03-04T10:00 - @2021-03-04T12:30:15");
+    }
+
+    #[test]
+    fn round_trips_arrays_and_maps() {
+        for source in [
+            "null",
+            "[]",
+            "{}",
+            "[[1, 2], [3.5, [4]], 'x', null, @2021-01-01]",
+            "{ 'salary': 300, 'age': 22.5, 'tags': ['a', 'b'], 'contract': { 'hours': 40, 'remote': null } }",
+            "{ 2021: 10, -3: 'neg', @2021-01-02: 1, [2021, 'ops']: 5, [[1, 'a'], @2020-01-01]: 6 }",
+            "[ { 'salary': 500, 'age': 22 }, { 'salary': 1000.5, 'age': 25, 'note': 'it''s' } ]"
+        ] {
+            assert_source_round_trip(source);
+        }
+    }
+}
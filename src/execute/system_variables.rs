@@ -0,0 +1,22 @@
+use super::structures::ExecuteOutput;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+// System variables (names starting with ⎕) configure how verbs behave, e.g. ⎕fill. Verbs are
+// executed without access to the program state, so these are kept separately where any verb can read them
+thread_local! {
+    static SYSTEM_VARIABLES: RefCell<HashMap<String, ExecuteOutput>> = RefCell::new(HashMap::new());
+}
+
+pub fn is_system_variable(name: &str) -> bool {
+    name.starts_with('⎕')
+}
+
+pub fn set_system_variable(name: String, value: ExecuteOutput) {
+    SYSTEM_VARIABLES.with(|vars| vars.borrow_mut().insert(name, value));
+}
+
+// Unset system variables read as Null
+pub fn get_system_variable(name: &str) -> ExecuteOutput {
+    SYSTEM_VARIABLES.with(|vars| vars.borrow().get(name).cloned().unwrap_or(ExecuteOutput::Null))
+}
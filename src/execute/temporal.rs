@@ -0,0 +1,184 @@
+use super::structures::ExecuteOutput;
+use crate::parse::structures::{Numeric, MapKey};
+use crate::parse::datetime::{Date, DateTime, Duration, SECONDS_PER_DAY};
+use std::cmp::Ordering;
+use indexmap::IndexMap;
+
+#[derive(Debug, Clone, Copy)]
+pub enum DatePart {
+    Year,
+    Month,
+    Day,
+    Weekday
+}
+
+pub fn is_temporal(val: &ExecuteOutput) -> bool {
+    matches!(val, ExecuteOutput::Date(_) | ExecuteOutput::DateTime(_) | ExecuteOutput::Duration(_))
+}
+
+// Apply a verb where either side is a date, time or duration. An array on the other side has the
+// verb applied to each of its values, otherwise the pair is combined by the temporal verb
+pub fn apply_temporal<V, T>(lhs: ExecuteOutput, rhs: ExecuteOutput, verb: V, temporal_verb: T) -> ExecuteOutput
+where V: Fn(ExecuteOutput, ExecuteOutput) -> ExecuteOutput, T: Fn(ExecuteOutput, ExecuteOutput) -> ExecuteOutput {
+    match (lhs, rhs) {
+        (ExecuteOutput::Array (array), temporal) => ExecuteOutput::Array(array.into_iter().map(|x| verb(x, temporal.clone())).collect()),
+        (temporal, ExecuteOutput::Array (array)) => ExecuteOutput::Array(array.into_iter().map(|x| verb(temporal.clone(), x)).collect()),
+        (lhs, rhs) => temporal_verb(lhs, rhs)
+    }
+}
+
+// Adding a duration moves a date or time along. A date moved by whole days stays a date, otherwise
+// it becomes a date time at midnight plus the duration
+pub fn execute_temporal_add(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    match (lhs, rhs) {
+        (ExecuteOutput::Date (date), ExecuteOutput::Duration (duration))
+            | (ExecuteOutput::Duration (duration), ExecuteOutput::Date (date)) => shift_date(date, duration.seconds()),
+        (ExecuteOutput::DateTime (datetime), ExecuteOutput::Duration (duration))
+            | (ExecuteOutput::Duration (duration), ExecuteOutput::DateTime (datetime)) => {
+            ExecuteOutput::DateTime(DateTime::from_seconds(datetime.seconds() + duration.seconds()))
+        },
+        (ExecuteOutput::Duration (lhs_duration), ExecuteOutput::Duration (rhs_duration)) => {
+            ExecuteOutput::Duration(Duration::from_seconds(lhs_duration.seconds() + rhs_duration.seconds()))
+        },
+        (lhs_other, rhs_other) => panic!("Cannot add pair ({:?}, {:?})", lhs_other, rhs_other)
+    }
+}
+
+// Subtracting two dates or times gives the duration between them, subtracting a duration moves back
+pub fn execute_temporal_subtract(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    match (lhs, rhs) {
+        (ExecuteOutput::Date (date), ExecuteOutput::Duration (duration)) => shift_date(date, -duration.seconds()),
+        (ExecuteOutput::DateTime (datetime), ExecuteOutput::Duration (duration)) => {
+            ExecuteOutput::DateTime(DateTime::from_seconds(datetime.seconds() - duration.seconds()))
+        },
+        (ExecuteOutput::Duration (lhs_duration), ExecuteOutput::Duration (rhs_duration)) => {
+            ExecuteOutput::Duration(Duration::from_seconds(lhs_duration.seconds() - rhs_duration.seconds()))
+        },
+        (lhs, rhs) => match (as_seconds_since_epoch(&lhs), as_seconds_since_epoch(&rhs)) {
+            (Some (lhs_seconds), Some (rhs_seconds)) => ExecuteOutput::Duration(Duration::from_seconds(lhs_seconds - rhs_seconds)),
+            _ => panic!("Cannot subtract pair ({:?}, {:?})", lhs, rhs)
+        }
+    }
+}
+
+// Durations can be scaled by a number, e.g. @P1D × 7
+pub fn execute_temporal_multiply(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    match (lhs, rhs) {
+        (ExecuteOutput::Duration (duration), ExecuteOutput::Numeric (numeric))
+            | (ExecuteOutput::Numeric (numeric), ExecuteOutput::Duration (duration)) => {
+            ExecuteOutput::Duration(Duration::from_seconds(scale_seconds(duration.seconds(), numeric.as_float())))
+        },
+        (lhs_other, rhs_other) => panic!("Cannot multiply pair ({:?}, {:?})", lhs_other, rhs_other)
+    }
+}
+
+// A duration divided by a number is a shorter duration, e.g. for averages, and a duration divided
+// by a duration is the number of times one fits into the other
+pub fn execute_temporal_divide(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    match (lhs, rhs) {
+        (ExecuteOutput::Duration (duration), ExecuteOutput::Numeric (numeric)) => {
+            if numeric.as_float() == 0.0 {
+                panic!("DOMAIN ERROR: cannot divide duration {:?} by zero", duration);
+            }
+
+            ExecuteOutput::Duration(Duration::from_seconds(scale_seconds(duration.seconds(), 1.0 / numeric.as_float())))
+        },
+        (ExecuteOutput::Duration (lhs_duration), ExecuteOutput::Duration (rhs_duration)) => {
+            if rhs_duration.seconds() == 0 {
+                panic!("DOMAIN ERROR: cannot divide duration {:?} by an empty duration", lhs_duration);
+            }
+
+            let divided = Numeric::Int(lhs_duration.seconds()) / Numeric::Int(rhs_duration.seconds());
+            ExecuteOutput::Numeric(divided)
+        },
+        (lhs_other, rhs_other) => panic!("Cannot divide pair ({:?}, {:?})", lhs_other, rhs_other)
+    }
+}
+
+// Order two dates, times or durations. A date compares as midnight when compared to a date time
+pub fn compare_temporal(lhs: &ExecuteOutput, rhs: &ExecuteOutput) -> Ordering {
+    match (lhs, rhs) {
+        (ExecuteOutput::Duration (lhs_duration), ExecuteOutput::Duration (rhs_duration)) => lhs_duration.cmp(rhs_duration),
+        (lhs, rhs) => match (as_seconds_since_epoch(lhs), as_seconds_since_epoch(rhs)) {
+            (Some (lhs_seconds), Some (rhs_seconds)) => lhs_seconds.cmp(&rhs_seconds),
+            _ => panic!("Cannot compare {:?} and {:?}", lhs, rhs)
+        }
+    }
+}
+
+// Pervasive extraction of part of a date, giving an int for each date or date time found
+pub fn execute_date_part(part: DatePart, rhs: ExecuteOutput) -> ExecuteOutput {
+    let date = match rhs {
+        ExecuteOutput::Date (date) => date,
+        ExecuteOutput::DateTime (datetime) => datetime.date(),
+        ExecuteOutput::Null => return ExecuteOutput::Null,
+        ExecuteOutput::Array (arr) => return ExecuteOutput::Array(arr.into_iter().map(|x| execute_date_part(part, x)).collect()),
+        ExecuteOutput::Map (dict) => {
+            let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+            for (key, value) in dict {
+                output.insert(key, execute_date_part(part, value));
+            }
+
+            return ExecuteOutput::Map(output);
+        },
+        other => panic!("Cannot take {:?} of {:?}", part, other)
+    };
+
+    let value = match part {
+        DatePart::Year => date.year(),
+        DatePart::Month => date.month(),
+        DatePart::Day => date.day(),
+        DatePart::Weekday => date.weekday()
+    };
+
+    ExecuteOutput::Numeric(Numeric::Int(value))
+}
+
+fn shift_date(date: Date, seconds: i64) -> ExecuteOutput {
+    match seconds % SECONDS_PER_DAY {
+        0 => ExecuteOutput::Date(Date::from_days(date.days() + seconds / SECONDS_PER_DAY)),
+        _ => ExecuteOutput::DateTime(DateTime::from_seconds(DateTime::from_date(date).seconds() + seconds))
+    }
+}
+
+fn as_seconds_since_epoch(val: &ExecuteOutput) -> Option<i64> {
+    match val {
+        ExecuteOutput::Date (date) => Some(DateTime::from_date(*date).seconds()),
+        ExecuteOutput::DateTime (datetime) => Some(datetime.seconds()),
+        _ => None
+    }
+}
+
+// Durations are whole seconds, so round after scaling
+fn scale_seconds(seconds: i64, factor: f64) -> i64 {
+    (seconds as f64 * factor).round() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    const DATES: &str = "d <- [@2021-03-04, @2021-03-10]\n";
+
+    fn evaluate_with_dates(source: &str) -> String {
+        evaluate(&(DATES.to_string() + source)).to_string()
+    }
+
+    #[test]
+    fn applies_to_every_value_of_an_array() {
+        assert_eq!(evaluate_with_dates("d + @P1D"), "[@2021-03-05, @2021-03-11]");
+        assert_eq!(evaluate_with_dates("@PT12H + d"), "[@2021-03-04T12:00:00, @2021-03-10T12:00:00]");
+        assert_eq!(evaluate_with_dates("d - @2021-03-01"), "[@P3D, @P9D]");
+        assert_eq!(evaluate_with_dates("[@P1D, @PT6H] × 2"), "[@P2D, @PT12H]");
+        assert_eq!(evaluate_with_dates("[@P1D, @PT6H] ÷ @PT6H"), "[4, 1]");
+        assert_eq!(evaluate_with_dates("d = @2021-03-10"), "[0, 1]");
+        assert_eq!(evaluate_with_dates("@2021-03-05 > d"), "[1, 0]");
+    }
+
+    #[test]
+    #[should_panic(expected = "DOMAIN ERROR: cannot divide duration P1D by an empty duration")]
+    fn rejects_dividing_by_an_empty_duration() {
+        evaluate("@P1D ÷ @PT0S");
+    }
+}
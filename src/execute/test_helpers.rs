@@ -0,0 +1,20 @@
+use super::structures::ExecuteOutput;
+use super::execute::{execute_program, execute_expression};
+use crate::parse::build_ast::parse_program;
+use crate::parse::structures::AstNode;
+use std::collections::HashMap;
+
+// Parse and run MapLang source, which must be parsed in full, giving the value of its last line.
+// Earlier lines run first, so tests can set up variables e.g. "x <- [1, 2]\n+/ x"
+pub fn evaluate(source: &str) -> ExecuteOutput {
+    let mut program = parse_program(source).unwrap_or_else(|err| panic!("Cannot parse {:?}: {}", source, err));
+
+    let last = match program.pop() {
+        Some (AstNode::Node (inner)) => *inner,
+        other => panic!("Expected an expression to evaluate, found {:?}", other)
+    };
+
+    let mut state = execute_program(program, HashMap::new());
+
+    execute_expression(last, &mut state)
+}
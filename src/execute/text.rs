@@ -0,0 +1,237 @@
+use super::structures::ExecuteOutput;
+use super::dict_keys::align_dicts;
+use crate::parse::structures::{Numeric, DyadicVerb, MapKey};
+use indexmap::IndexMap;
+
+// Text verbs take the strings to work on as their lhs (or only) argument, and pervade through
+// arrays and map values so whole columns can be cleaned at once, e.g. names replace ['  ', ' ']
+
+pub fn execute_length(rhs: ExecuteOutput) -> ExecuteOutput {
+    pervade_text(rhs, "length", &|text| ExecuteOutput::Numeric(Numeric::Int(text.chars().count() as i64)))
+}
+
+pub fn execute_upper(rhs: ExecuteOutput) -> ExecuteOutput {
+    pervade_text(rhs, "upper", &|text| ExecuteOutput::String(text.to_uppercase()))
+}
+
+pub fn execute_lower(rhs: ExecuteOutput) -> ExecuteOutput {
+    pervade_text(rhs, "lower", &|text| ExecuteOutput::String(text.to_lowercase()))
+}
+
+pub fn execute_trim(rhs: ExecuteOutput) -> ExecuteOutput {
+    pervade_text(rhs, "trim", &|text| ExecuteOutput::String(text.trim().to_string()))
+}
+
+// Join strings end to end, pairwise for two arrays or maps, or against a single string
+pub fn execute_concat(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    match (lhs, rhs) {
+        // Nulls propagate through the verb
+        (ExecuteOutput::Null, _) | (_, ExecuteOutput::Null) => ExecuteOutput::Null,
+        (ExecuteOutput::String (lhs_string), ExecuteOutput::String (rhs_string)) => ExecuteOutput::String(lhs_string + &rhs_string),
+        (ExecuteOutput::Array (lhs_array), ExecuteOutput::Array (rhs_array)) => {
+            if lhs_array.len() != rhs_array.len() {
+                panic!("Cannot concat arrays of different lengths {:?} vs {:?}", lhs_array.len(), rhs_array.len());
+            }
+
+            let output = lhs_array.into_iter().zip(rhs_array).map(|(lhs_val, rhs_val)| execute_concat(lhs_val, rhs_val)).collect();
+
+            ExecuteOutput::Array(output)
+        },
+        (ExecuteOutput::Map (lhs_map), ExecuteOutput::Map (rhs_map)) => {
+            let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+            for (key, lhs_value, rhs_value) in align_dicts(DyadicVerb::Concat, lhs_map, rhs_map) {
+                output.insert(key, execute_concat(lhs_value, rhs_value));
+            }
+
+            ExecuteOutput::Map(output)
+        },
+        (ExecuteOutput::Array (lhs_array), rhs_string @ ExecuteOutput::String (_)) => {
+            ExecuteOutput::Array(lhs_array.into_iter().map(|x| execute_concat(x, rhs_string.clone())).collect())
+        },
+        (lhs_string @ ExecuteOutput::String (_), ExecuteOutput::Array (rhs_array)) => {
+            ExecuteOutput::Array(rhs_array.into_iter().map(|x| execute_concat(lhs_string.clone(), x)).collect())
+        },
+        (ExecuteOutput::Map (lhs_map), rhs_string @ ExecuteOutput::String (_)) => {
+            map_values(lhs_map, |x| execute_concat(x, rhs_string.clone()))
+        },
+        (lhs_string @ ExecuteOutput::String (_), ExecuteOutput::Map (rhs_map)) => {
+            map_values(rhs_map, |x| execute_concat(lhs_string.clone(), x))
+        },
+        (lhs_other, rhs_other) => panic!("Cannot concat pair ({:?}, {:?})", lhs_other, rhs_other)
+    }
+}
+
+// Split strings on a separator, giving an array of strings for each
+pub fn execute_split(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    let separator = expect_string(rhs, "split");
+
+    if separator.is_empty() {
+        panic!("Cannot split on an empty separator");
+    }
+
+    pervade_text(lhs, "split", &|text| {
+        ExecuteOutput::Array(text.split(separator.as_str()).map(|part| ExecuteOutput::String(part.to_string())).collect())
+    })
+}
+
+// Join an array of strings into one string with a separator between each. Arrays of arrays and
+// maps of arrays are joined one array at a time
+pub fn execute_join(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    let separator = expect_string(rhs, "join");
+
+    join_with(lhs, &separator)
+}
+
+fn join_with(lhs: ExecuteOutput, separator: &str) -> ExecuteOutput {
+    match lhs {
+        ExecuteOutput::Null => ExecuteOutput::Null,
+        ExecuteOutput::Array (arr) if arr.iter().all(|x| matches!(x, ExecuteOutput::String(_))) => {
+            let parts: Vec<String> = arr.into_iter().map(|x| expect_string(x, "join")).collect();
+
+            ExecuteOutput::String(parts.join(separator))
+        },
+        ExecuteOutput::Array (arr) => ExecuteOutput::Array(arr.into_iter().map(|x| join_with(x, separator)).collect()),
+        ExecuteOutput::Map (dict) => map_values(dict, |x| join_with(x, separator)),
+        other => panic!("Cannot join {:?}, must be an array of strings", other)
+    }
+}
+
+// Take part of each string, given a start position or a [start, length] pair. Positions count
+// characters from 0 and are clipped to the end of the string
+pub fn execute_substring(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    let (start, length) = match rhs {
+        ExecuteOutput::Numeric (Numeric::Int(start)) if start >= 0 => (start as usize, None),
+        ExecuteOutput::Array (arr) => match arr.as_slice() {
+            [ExecuteOutput::Numeric (Numeric::Int(start)), ExecuteOutput::Numeric (Numeric::Int(length))] if *start >= 0 && *length >= 0 => {
+                (*start as usize, Some(*length as usize))
+            },
+            _ => panic!("Substring takes a start or [start, length] of non-negative ints, found {:?}", arr)
+        },
+        other => panic!("Substring takes a start or [start, length] of non-negative ints, found {:?}", other)
+    };
+
+    pervade_text(lhs, "substr", &|text| {
+        let chars = text.chars().skip(start);
+
+        let substring: String = match length {
+            Some (length) => chars.take(length).collect(),
+            None => chars.collect()
+        };
+
+        ExecuteOutput::String(substring)
+    })
+}
+
+// 1 where the string contains the rhs string, 0 otherwise
+pub fn execute_contains(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    let needle = expect_string(rhs, "contains");
+
+    pervade_text(lhs, "contains", &|text| ExecuteOutput::Numeric(Numeric::Int(text.contains(needle.as_str()) as i64)))
+}
+
+// 1 where the string starts with the rhs string, 0 otherwise
+pub fn execute_starts_with(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    let prefix = expect_string(rhs, "startswith");
+
+    pervade_text(lhs, "startswith", &|text| ExecuteOutput::Numeric(Numeric::Int(text.starts_with(prefix.as_str()) as i64)))
+}
+
+// Replace every occurrence of a string, given as a [from, to] pair
+pub fn execute_replace(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    let (from, to) = match rhs {
+        ExecuteOutput::Array (arr) => match arr.as_slice() {
+            [ExecuteOutput::String (from), ExecuteOutput::String (to)] if !from.is_empty() => (from.clone(), to.clone()),
+            _ => panic!("Replace takes a [from, to] pair of strings, found {:?}", arr)
+        },
+        other => panic!("Replace takes a [from, to] pair of strings, found {:?}", other)
+    };
+
+    pervade_text(lhs, "replace", &|text| ExecuteOutput::String(text.replace(from.as_str(), &to)))
+}
+
+// Apply a function to every string, keeping the shape of arrays and maps around them
+fn pervade_text<F>(val: ExecuteOutput, verb_name: &str, text_fn: &F) -> ExecuteOutput
+where
+    F: Fn(String) -> ExecuteOutput
+{
+    match val {
+        ExecuteOutput::String (text) => text_fn(text),
+        ExecuteOutput::Null => ExecuteOutput::Null,
+        ExecuteOutput::Array (arr) => ExecuteOutput::Array(arr.into_iter().map(|x| pervade_text(x, verb_name, text_fn)).collect()),
+        ExecuteOutput::Map (dict) => map_values(dict, |x| pervade_text(x, verb_name, text_fn)),
+        other => panic!("Cannot {} {:?}, must be a string", verb_name, other)
+    }
+}
+
+fn map_values<F>(dict: IndexMap<MapKey, ExecuteOutput>, value_fn: F) -> ExecuteOutput
+where
+    F: Fn(ExecuteOutput) -> ExecuteOutput
+{
+    let mut output: IndexMap<MapKey, ExecuteOutput> = IndexMap::new();
+
+    for (key, value) in dict {
+        output.insert(key, value_fn(value));
+    }
+
+    ExecuteOutput::Map(output)
+}
+
+fn expect_string(val: ExecuteOutput, verb_name: &str) -> String {
+    match val {
+        ExecuteOutput::String (text) => text,
+        other => panic!("Expected a string for {}, found {:?}", verb_name, other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    const NAMES: &str = "names <- ['  Ada Lovelace ', 'alan turing']\n";
+
+    fn evaluate_with_names(source: &str) -> String {
+        evaluate(&(NAMES.to_string() + source)).to_string()
+    }
+
+    #[test]
+    fn cleans_strings() {
+        assert_eq!(evaluate_with_names("upper trim names"), "['ADA LOVELACE', 'ALAN TURING']");
+        assert_eq!(evaluate_with_names("lower names"), "['  ada lovelace ', 'alan turing']");
+        assert_eq!(evaluate("length ['café', '', null]").to_string(), "[4, 0, null]");
+    }
+
+    #[test]
+    fn splits_joins_and_concatenates() {
+        assert_eq!(evaluate_with_names("(trim names) split ' '"), "[['Ada', 'Lovelace'], ['alan', 'turing']]");
+        assert_eq!(evaluate_with_names("((trim names) split ' ') join '_'"), "['Ada_Lovelace', 'alan_turing']");
+        assert_eq!(evaluate("{ 'x': ['a', 'b'], 'y': ['c'] } join ', '").to_string(), "{'x': 'a, b', 'y': 'c'}");
+        assert_eq!(evaluate_with_names("'Dr ' concat trim names"), "['Dr Ada Lovelace', 'Dr alan turing']");
+        assert_eq!(evaluate("['a', 'b'] concat ['c', 'd']").to_string(), "['ac', 'bd']");
+    }
+
+    #[test]
+    fn takes_substrings_by_character() {
+        assert_eq!(evaluate_with_names("(trim names) substr [0, 3]"), "['Ada', 'ala']");
+        assert_eq!(evaluate("'héllo' substr 1").to_string(), "'éllo'");
+        assert_eq!(evaluate("'héllo' substr [3, 10]").to_string(), "'lo'");
+    }
+
+    #[test]
+    fn searches_and_replaces() {
+        assert_eq!(evaluate_with_names("(lower names) contains 'love'"), "[1, 0]");
+        assert_eq!(evaluate("['abc', 'bc'] startswith 'ab'").to_string(), "[1, 0]");
+        assert_eq!(evaluate("'a-b-a' replace ['a', 'z']").to_string(), "'z-b-z'");
+    }
+
+    #[test]
+    fn pervades_through_maps_and_nulls() {
+        assert_eq!(evaluate("trim { 'a': ' x ', 'b': [' y'], 'c': null }").to_string(), "{'a': 'x', 'b': ['y'], 'c': null}");
+    }
+
+    #[test]
+    #[should_panic(expected = "must be a string")]
+    fn rejects_values_that_are_not_strings() {
+        evaluate("length 5");
+    }
+}
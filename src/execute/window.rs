@@ -0,0 +1,241 @@
+use super::structures::ExecuteOutput;
+use super::operator_reduce::execute_reduce_dyadic_lhs;
+use super::dyadic_divide::execute_divide;
+use super::temporal::{is_temporal, compare_temporal};
+use super::map_key::to_map_key;
+use crate::parse::structures::{Numeric, DyadicVerb, MapKey};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+// Window functions compute one output per value of an array, looking at neighbouring values.
+// Over arrays of maps the lhs can be a map of options: 'n' the window size or offset, 'by' a key
+// to partition the records by (each partition is windowed separately) and 'on' a key to take
+// the values from, e.g. { 'n': 1, 'by': 'dept', 'on': 'salary' } lag employees
+struct WindowOptions {
+    size: usize,
+    by: Option<String>,
+    on: Option<String>
+}
+
+pub fn execute_lag(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    let options = window_options(lhs);
+    let offset = options.size;
+
+    apply_window(rhs, &options, |values| {
+        (0..values.len()).map(|i| match i >= offset {
+            true => values[i - offset].clone(),
+            false => ExecuteOutput::Null
+        }).collect()
+    })
+}
+
+pub fn execute_lead(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    let options = window_options(lhs);
+    let offset = options.size;
+
+    apply_window(rhs, &options, |values| {
+        (0..values.len()).map(|i| values.get(i + offset).cloned().unwrap_or(ExecuteOutput::Null)).collect()
+    })
+}
+
+// Mean of each value and the n - 1 values before it. The first few windows are shorter, so
+// average over however many values are available
+pub fn execute_moving_average(lhs: ExecuteOutput, rhs: ExecuteOutput) -> ExecuteOutput {
+    let options = window_options(lhs);
+    let size = options.size;
+
+    apply_window(rhs, &options, |values| {
+        (0..values.len()).map(|i| {
+            let window = values[(i + 1).saturating_sub(size)..=i].to_vec();
+            let count = ExecuteOutput::Numeric(Numeric::Int(window.len() as i64));
+            let total = execute_reduce_dyadic_lhs(DyadicVerb::Add, ExecuteOutput::Array(window));
+
+            execute_divide(total, count)
+        }).collect()
+    })
+}
+
+// Rank values in ascending order starting from 1. Ties share the lowest rank, and the next rank
+// either skips past the tie (1 2 2 4) or, with dense ranking, doesn't (1 2 2 3). Nulls aren't ranked
+pub fn execute_rank(lhs: Option<ExecuteOutput>, rhs: ExecuteOutput, dense: bool) -> ExecuteOutput {
+    let options = match lhs {
+        Some (lhs) => window_options(lhs),
+        None => default_window_options()
+    };
+
+    apply_window(rhs, &options, |values| {
+        let mut sorted: Vec<ExecuteOutput> = values.iter().filter(|x| !matches!(x, ExecuteOutput::Null)).cloned().collect();
+        sorted.sort_by(compare_values);
+
+        if dense {
+            sorted.dedup_by(|a, b| compare_values(a, b) == Ordering::Equal);
+        }
+
+        values.iter().map(|val| match val {
+            ExecuteOutput::Null => ExecuteOutput::Null,
+            val => {
+                let below = sorted.partition_point(|x| compare_values(x, val) == Ordering::Less);
+                ExecuteOutput::Numeric(Numeric::Int(below as i64 + 1))
+            }
+        }).collect()
+    })
+}
+
+// Running count of the values so far that are present, i.e. not null, so zeros count. Without
+// 'on', counting records gives the row number within each partition
+pub fn execute_cumulative_count(lhs: Option<ExecuteOutput>, rhs: ExecuteOutput) -> ExecuteOutput {
+    let options = match lhs {
+        Some (lhs) => window_options(lhs),
+        None => default_window_options()
+    };
+
+    apply_window(rhs, &options, |values| {
+        let mut count: i64 = 0;
+
+        values.iter().map(|val| {
+            if !matches!(val, ExecuteOutput::Null) {
+                count += 1;
+            }
+
+            ExecuteOutput::Numeric(Numeric::Int(count))
+        }).collect()
+    })
+}
+
+fn default_window_options() -> WindowOptions {
+    WindowOptions { size: 1, by: None, on: None }
+}
+
+fn window_options(lhs: ExecuteOutput) -> WindowOptions {
+    match lhs {
+        ExecuteOutput::Numeric (Numeric::Int(x)) if x > 0 => WindowOptions { size: x as usize, by: None, on: None },
+        ExecuteOutput::Map (options) => {
+            let mut window_options = default_window_options();
+
+            for (key, value) in options {
+                let option_name = match &key {
+                    MapKey::String (name) => name.as_str(),
+                    other => panic!("Unknown window option {:?}", other)
+                };
+
+                match (option_name, value) {
+                    ("n", ExecuteOutput::Numeric (Numeric::Int(x))) if x > 0 => window_options.size = x as usize,
+                    ("by", ExecuteOutput::String (by)) => window_options.by = Some(by),
+                    ("on", ExecuteOutput::String (on)) => window_options.on = Some(on),
+                    ("n", other) => panic!("Window size 'n' must be a positive int, found {:?}", other),
+                    (other_key, other_value) => panic!("Unknown window option {:?}: {:?}", other_key, other_value)
+                }
+            }
+
+            window_options
+        },
+        other => panic!("Window options must be a positive int or map of options, found {:?}", other)
+    }
+}
+
+// Apply a window function to each partition of the values, putting the results back in the
+// position of the value they were computed for
+fn apply_window<F>(rhs: ExecuteOutput, options: &WindowOptions, window_fn: F) -> ExecuteOutput
+where
+    F: Fn(Vec<ExecuteOutput>) -> Vec<ExecuteOutput>
+{
+    let rows = match rhs {
+        ExecuteOutput::Array (arr) => arr,
+        other => panic!("Window functions can only be used over arrays, found {:?}", other)
+    };
+
+    let values: Vec<ExecuteOutput> = match &options.on {
+        Some (key) => rows.iter().map(|row| record_field(row, key)).collect(),
+        None => rows.clone()
+    };
+
+    let mut output: Vec<ExecuteOutput> = vec![ExecuteOutput::Null; rows.len()];
+
+    for indices in partition_indices(&rows, &options.by) {
+        let partition_values = indices.iter().map(|i| values[*i].clone()).collect();
+
+        for (i, val) in indices.iter().zip(window_fn(partition_values)) {
+            output[*i] = val;
+        }
+    }
+
+    ExecuteOutput::Array(output)
+}
+
+// Group the positions of the rows by the value of the partition key, in order of first appearance.
+// Rows without the key, or where it is null, form a partition of their own
+fn partition_indices(rows: &[ExecuteOutput], by: &Option<String>) -> Vec<Vec<usize>> {
+    let key = match by {
+        Some (key) => key,
+        None => return vec![(0..rows.len()).collect()]
+    };
+
+    let mut partitions: Vec<Vec<usize>> = Vec::new();
+    let mut partition_positions: HashMap<Option<MapKey>, usize> = HashMap::new();
+
+    for (i, row) in rows.iter().enumerate() {
+        let partition = match record_field(row, key) {
+            ExecuteOutput::Null => None,
+            value => Some(to_map_key(value))
+        };
+
+        match partition_positions.get(&partition) {
+            Some (position) => partitions[*position].push(i),
+            None => {
+                partition_positions.insert(partition, partitions.len());
+                partitions.push(vec![i]);
+            }
+        }
+    }
+
+    partitions
+}
+
+fn record_field(row: &ExecuteOutput, key: &str) -> ExecuteOutput {
+    match row {
+        ExecuteOutput::Map (dict) => dict.get(&MapKey::from(key)).cloned().unwrap_or(ExecuteOutput::Null),
+        other => panic!("Cannot window over key {:?} of {:?}, must be an array of maps", key, other)
+    }
+}
+
+fn compare_values(lhs: &ExecuteOutput, rhs: &ExecuteOutput) -> Ordering {
+    match (lhs, rhs) {
+        (ExecuteOutput::Numeric (lhs_numeric), ExecuteOutput::Numeric (rhs_numeric)) => {
+            lhs_numeric.partial_cmp(rhs_numeric).unwrap_or(Ordering::Equal)
+        },
+        (ExecuteOutput::String (lhs_string), ExecuteOutput::String (rhs_string)) => lhs_string.cmp(rhs_string),
+        (lhs_temporal, rhs_temporal) if is_temporal(lhs_temporal) && is_temporal(rhs_temporal) => compare_temporal(lhs_temporal, rhs_temporal),
+        (lhs_other, rhs_other) => panic!("Cannot order {:?} and {:?}", lhs_other, rhs_other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::test_helpers::evaluate;
+
+    #[test]
+    fn partitions_by_key_values() {
+        let records = "x <- [{ 'dept': 'a', 'v': 1 }, { 'dept': 'b', 'v': 2 }, { 'v': 3 }, { 'dept': 'a', 'v': 4 }, { 'dept': null, 'v': 5 }]\n";
+
+        assert_eq!(evaluate(&(records.to_string() + "{ 'by': 'dept', 'on': 'v' } cumcount x")).to_string(), "[1, 1, 1, 2, 2]");
+        assert_eq!(evaluate(&(records.to_string() + "{ 'n': 1, 'by': 'dept', 'on': 'v' } lag x")).to_string(), "[null, null, null, 1, 3]");
+    }
+
+    #[test]
+    fn counts_values_that_are_not_null() {
+        assert_eq!(evaluate("cumcount [5, null, 0, 0.0, 'x']").to_string(), "[1, 1, 2, 3, 4]");
+        assert_eq!(evaluate("{ 'on': 'v' } cumcount [{ 'v': 0 }, { 'w': 1 }, { 'v': 2 }]").to_string(), "[1, 1, 2]");
+    }
+
+    #[test]
+    #[should_panic(expected = "must be a positive int")]
+    fn rejects_empty_windows() {
+        evaluate("0 movavg [1, 2, 3]");
+    }
+
+    #[test]
+    #[should_panic(expected = "must be a positive int")]
+    fn rejects_empty_window_options() {
+        evaluate("{ 'n': 0 } movavg [1, 2, 3]");
+    }
+}
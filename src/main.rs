@@ -2,63 +2,105 @@ mod parse;
 mod execute;
 
 use parse::structures::AstNode;
-use parse::build_ast::{Rule, ArrayLanguageParser, build_ast_from_expr};
+use parse::build_ast::parse_program;
 use execute::structures::ExecuteOutput;
-use execute::execute::execute_expression;
+use execute::execute::execute_program;
+use execute::modules::enter_file;
+use execute::stream::stream_ndjson;
+use execute::system_variables::set_system_variable;
+use execute::csv::infer_value;
 
 extern crate pest;
 #[macro_use]
 extern crate pest_derive;
 
-use pest::Parser;
-use pest::error::Error;
 use std::fs;
+use std::path::Path;
+use std::io;
 use std::collections::HashMap;
 
-fn parse(source: &str) -> Result<Vec<AstNode>, Error<Rule>> {
-    let mut ast = vec![];
+// Run all but the last expression of the program, then stream each line of NDJSON from the input
+// file (or stdin when there isn't one, or it is -) through the last expression, writing NDJSON to stdout
+fn run_stream(source: &str, input_path: Option<&String>, state: HashMap<String, ExecuteOutput>) {
+    let mut program = parse_program(source).unwrap();
+
+    let expression = match program.pop() {
+        Some (AstNode::Node (inner)) => *inner,
+        _ => panic!("Streaming needs an expression to apply to each record")
+    };
+
+    let state = execute_program(program, state);
+    let stdout = io::stdout();
+    let mut output = io::BufWriter::new(stdout.lock());
+
+    match input_path.filter(|path| *path != "-") {
+        Some (path) => {
+            let file = fs::File::open(path).unwrap_or_else(|err| panic!("Cannot open NDJSON input {:?}: {}", path, err));
+            stream_ndjson(expression, state, io::BufReader::new(file), &mut output);
+        },
+        None => stream_ndjson(expression, state, io::stdin().lock(), &mut output)
+    }
+}
 
-    // Recursively build up tree to be executed
-    let pairs = ArrayLanguageParser::parse(Rule::program, source)?;
+// Bind --param name=value as the variable name, with the value read as an int or float if it
+// looks like one and a string otherwise, e.g. --param threshold=300
+fn bind_param(param: &str, state: &mut HashMap<String, ExecuteOutput>) {
+    let (name, value) = match param.split_once('=') {
+        Some (pair) => pair,
+        None => panic!("Parameter {:?} must be written name=value", param)
+    };
 
-    println!("{:?}", pairs);
+    let is_name = name.starts_with(|c: char| c.is_ascii_alphabetic()) && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
 
-    for pair in pairs {
-        match pair.as_rule() {
-            Rule::expression => {
-                ast.push(AstNode::Node(Box::new(build_ast_from_expr(pair))));
-            }
-            _ => {}
-        }
+    if !is_name {
+        panic!("Parameter name {:?} must be a variable name", name);
     }
 
-    Ok(ast)
+    state.insert(name.to_string(), infer_value(value.to_string()));
 }
 
-fn run_program(program: Vec<AstNode>) {
+// toy_language_2 [script] [args...] runs the script (test_program_2.map by default), with the
+// remaining arguments in ⎕args. toy_language_2 --ndjson <program> [input] [args...] streams newline
+// delimited JSON through the program, e.g. --ndjson "+/ ⍵.'bytes'" logs.ndjson. Either way
+// --param name=value binds a variable before the program runs
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut positional: Vec<String> = Vec::new();
     let mut state: HashMap<String, ExecuteOutput> = HashMap::new();
+    let mut stream_source: Option<String> = None;
 
-    // run over nodes 'line by line' executing each line
-    for line in program {
-        let node = match line {
-            AstNode::Node(inner) => {
-                *inner
+    while let Some (arg) = args.next() {
+        match arg.as_str() {
+            "--param" => match args.next() {
+                Some (param) => bind_param(&param, &mut state),
+                None => panic!("Usage: --param name=value")
+            },
+            "--ndjson" => match args.next() {
+                Some (source) => stream_source = Some(source),
+                None => panic!("Usage: --ndjson <program> [input]")
             },
-            node_matched => panic!("Unexpected node type in run program {:?}", node_matched)
-        };
+            _ => positional.push(arg)
+        }
+    }
+
+    let mut positional = positional.into_iter();
+
+    if let Some (source) = stream_source {
+        let input_path = positional.next();
+        set_system_variable("⎕args".to_string(), ExecuteOutput::Array(positional.map(ExecuteOutput::String).collect()));
 
-        // TODO: should we handle the assignment of variables out here instead? separately, then pass an immutable state in...
-        execute_expression(node, &mut state);
+        return run_stream(&source, input_path.as_ref(), state);
     }
-}
 
-fn main() {
-    let unparsed_file = fs::read_to_string("test_program_2.map").expect("cannot read file");
+    let script_path = positional.next().unwrap_or_else(|| "test_program_2.map".to_string());
+    set_system_variable("⎕args".to_string(), ExecuteOutput::Array(positional.map(ExecuteOutput::String).collect()));
 
-    let out = parse(&unparsed_file).unwrap();
+    let unparsed_file = fs::read_to_string(&script_path).unwrap_or_else(|err| panic!("Cannot read script {:?}: {}", script_path, err));
 
-    println!("{:?}", out);
+    let out = parse_program(&unparsed_file).unwrap();
 
-    run_program(out);
+    // Imports are found relative to the script
+    enter_file(Path::new(&script_path));
+    execute_program(out, state);
 }
 
@@ -1,10 +1,30 @@
-use std::collections::HashMap;
+use indexmap::IndexMap;
+use pest::Parser;
 use super::structures::*;
+use super::decimal::Decimal;
+use super::datetime::{Date, DateTime, Duration};
 
 #[derive(Parser)]
 #[grammar = "language_grammar.pest"]
 pub struct ArrayLanguageParser;
 
+// Parse errors are returned as pest builds them, to be shown with the failing line and column
+#[allow(clippy::result_large_err)]
+pub fn parse_program(source: &str) -> Result<Vec<AstNode>, pest::error::Error<Rule>> {
+    let mut ast = vec![];
+
+    // Recursively build up tree to be executed
+    let pairs = ArrayLanguageParser::parse(Rule::program, source)?;
+
+    for pair in pairs {
+        if pair.as_rule() == Rule::expression {
+            ast.push(AstNode::Node(Box::new(build_ast_from_expr(pair))));
+        }
+    }
+
+    Ok(ast)
+}
+
 pub fn build_ast_from_expr(pair: pest::iterators::Pair<Rule>) -> AstNode {
     match pair.as_rule() {
         Rule::expression => build_ast_from_expr(pair.into_inner().next().unwrap()),
@@ -42,6 +62,29 @@ pub fn build_ast_from_expr(pair: pest::iterators::Pair<Rule>) -> AstNode {
 
             parse_operator_verb(lhs_verb, operator_verb, rhs)
         },
+        Rule::dyadicOperatorExpression => {
+            let mut pair = pair.into_inner();
+
+            let lhs = pair.next().unwrap();
+            let lhs = build_ast_from_expr(lhs);
+
+            let lhs_verb = pair.next().unwrap();
+
+            let operator_verb = pair.next().unwrap();
+
+            let rhs = pair.next().unwrap();
+            let rhs = build_ast_from_expr(rhs);
+
+            match parse_operator_verb(lhs_verb, operator_verb, rhs) {
+                AstNode::OperatorOp {lhs_verb, operator_verb, rhs} => AstNode::DyadicOperatorOp {
+                    lhs_verb,
+                    operator_verb,
+                    lhs: Box::new(lhs),
+                    rhs
+                },
+                other => panic!("Expected operator node, found {:?}", other)
+            }
+        },
         Rule::assignment => {
             let mut pair = pair.into_inner();
             let variable = pair.next().unwrap();
@@ -52,12 +95,19 @@ pub fn build_ast_from_expr(pair: pest::iterators::Pair<Rule>) -> AstNode {
                 expression: Box::new(expression)
             }
         },
+        Rule::importStatement => {
+            let mut pair = pair.into_inner().skip(1);
+            let path = parse_string_literal(pair.next().unwrap().as_str());
+            let namespace = pair.next().map(|variable| variable.as_str().to_string());
+
+            AstNode::Import { path, namespace }
+        },
         Rule::terms => {
             let terms: Vec<AstNode> = pair.into_inner().map(build_ast_from_term).collect();
 
             // If single item, then unwrap it from vector
             match terms.len() {
-                1 => terms.get(0).unwrap().clone(),
+                1 => terms.first().unwrap().clone(),
                 _ => AstNode::Terms(terms)
             }
         },
@@ -69,43 +119,46 @@ fn build_ast_from_term(pair: pest::iterators::Pair<Rule>) -> AstNode {
     match pair.as_rule() {
         Rule::integer => {
             let istr = pair.as_str();
-            let integer: i64 = istr.parse().unwrap();
-            AstNode::Numeric(Numeric::Int(integer))
+
+            // Literals too large for 64 bits become big ints
+            match istr.parse::<i64>() {
+                Ok (integer) => AstNode::Numeric(Numeric::Int(integer)),
+                Err (_) => AstNode::Numeric(Numeric::BigInt(istr.parse().unwrap()))
+            }
         },
         Rule::decimal => {
             let fstr = pair.as_str();
             let float: f64 = fstr.parse().unwrap();
             AstNode::Numeric(Numeric::Float(float))
         },
-        Rule::string => {
-            // String first and last char as they are "'" characters
-            let mut chars = pair.as_str().chars();
-            chars.next();
-            chars.next_back();
-
-            AstNode::String(chars.as_str().to_string())
+        Rule::exactDecimal => {
+            // Strip the d suffix
+            let dstr = pair.as_str().trim_end_matches('d');
+            AstNode::Numeric(Numeric::Decimal(Decimal::parse(dstr)))
         },
+        // Strip the @ prefix from dates, times and durations
+        Rule::date => AstNode::Date(Date::parse(&pair.as_str()[1..])),
+        Rule::dateTime => AstNode::DateTime(DateTime::parse(&pair.as_str()[1..])),
+        Rule::duration => AstNode::Duration(Duration::parse(&pair.as_str()[1..])),
+        Rule::string => AstNode::String(parse_string_literal(pair.as_str())),
+        Rule::null => AstNode::Null,
         Rule::array => {
             let vals: Vec<AstNode> = pair.into_inner().map(build_ast_from_term).collect();
 
             AstNode::Array(vals)
         },
         Rule::map => {
-            let mut map: HashMap<String, AstNode> = HashMap::new();
+            let mut map: IndexMap<MapKey, AstNode> = IndexMap::new();
             
             for entry in pair.into_inner() {
                 let mut entry = entry.into_inner();
 
-                // Get variable name, strip first and last char as they are "'" characters
-                let mut var = entry.next().unwrap().as_str().chars();
-                var.next();
-                var.next_back();
-                let var = var.as_str().to_string();
+                let key = map_key_from_pair(entry.next().unwrap());
 
                 let expr = entry.next().unwrap();
                 let expr = build_ast_from_expr(expr);
                 
-                map.insert(var, expr);
+                map.insert(key, expr);
             }
 
             AstNode::Map(map)
@@ -113,18 +166,66 @@ fn build_ast_from_term(pair: pest::iterators::Pair<Rule>) -> AstNode {
         Rule::expression => {
             build_ast_from_expr(pair)
         },
-        Rule::variable => {
+        Rule::variable | Rule::systemVariable => {
             AstNode::Variable(pair.as_str().to_string())
         },
+        Rule::function => {
+            let source = pair.as_str().to_string();
+            let body: Vec<AstNode> = pair.into_inner().map(build_ast_from_expr).collect();
+
+            AstNode::Function { source, body }
+        },
+        Rule::call => {
+            let mut pair = pair.into_inner();
+
+            // A function from a map is looked up as the key of the same name, e.g. stats.mean
+            let mut callee = pair.next().unwrap().into_inner().map(|name| name.as_str().to_string());
+            let variable = AstNode::Variable(callee.next().unwrap());
+            let function = match callee.next() {
+                Some (name) => AstNode::DyadicOp { verb: DyadicVerb::Access, lhs: Box::new(variable), rhs: Box::new(AstNode::String(name)) },
+                None => variable
+            };
+
+            AstNode::Call { function: Box::new(function), args: pair.map(build_ast_from_expr).collect() }
+        },
         unknown_term => panic!("Unexpected term: {:?}", unknown_term)
     }
 }
 
+fn map_key_from_pair(pair: pest::iterators::Pair<Rule>) -> MapKey {
+    match pair.as_rule() {
+        Rule::string => MapKey::String(parse_string_literal(pair.as_str())),
+        Rule::integer => match pair.as_str().parse::<i64>() {
+            Ok (integer) => MapKey::Int(integer),
+            Err (_) => panic!("Map key {} is too large, int keys must fit in 64 bits", pair.as_str())
+        },
+        Rule::date => MapKey::Date(Date::parse(&pair.as_str()[1..])),
+        Rule::compositeKey => MapKey::Composite(pair.into_inner().map(map_key_from_pair).collect()),
+        unknown_key => panic!("Unexpected map key: {:?}", unknown_key)
+    }
+}
+
 fn parse_dyadic_verb(lhs: AstNode, pair: pest::iterators::Pair<Rule>, rhs: AstNode) -> AstNode {
     AstNode::DyadicOp {
         lhs: Box::new(lhs),
         rhs: Box::new(rhs),
-        verb: dyadic_verb_from_str(pair.as_str())
+        verb: dyadic_verb_from_pair(pair)
+    }
+}
+
+fn dyadic_verb_from_pair(pair: pest::iterators::Pair<Rule>) -> DyadicVerb {
+    match pair.as_rule() {
+        Rule::mergeVerb => {
+            let combine_verb = pair.into_inner().next().unwrap();
+
+            DyadicVerb::MergeWith(Box::new(dyadic_verb_from_str(combine_verb.as_str())))
+        },
+        Rule::intersectVerb => {
+            let combine_verb = pair.into_inner().next().unwrap();
+
+            DyadicVerb::IntersectWith(Box::new(dyadic_verb_from_str(combine_verb.as_str())))
+        },
+        _ => dyadic_verb_from_str(pair.as_str())
     }
 }
 
@@ -133,11 +234,42 @@ fn dyadic_verb_from_str(verb_str: &str) -> DyadicVerb {
         "+" => DyadicVerb::Add,
         "-" => DyadicVerb::Subtract,
         "/" => DyadicVerb::Replicate,
+        "⍴" => DyadicVerb::Reshape,
         ">" => DyadicVerb::GreaterThan,
         "÷" => DyadicVerb::Divide,
         "×" => DyadicVerb::Multiply,
         "." => DyadicVerb::Access,
         "=" => DyadicVerb::Equals,
+        "≠" => DyadicVerb::NotEquals,
+        "∊" => DyadicVerb::Member,
+        "∪" => DyadicVerb::Merge,
+        "∩" => DyadicVerb::Intersect,
+        "⊣" => DyadicVerb::Left,
+        "⊢" => DyadicVerb::Right,
+        "fill" => DyadicVerb::Fill,
+        "lag" => DyadicVerb::Lag,
+        "lead" => DyadicVerb::Lead,
+        "movavg" => DyadicVerb::MovingAverage,
+        "rank" => DyadicVerb::Rank,
+        "denserank" => DyadicVerb::DenseRank,
+        "cumcount" => DyadicVerb::CumulativeCount,
+        "concat" => DyadicVerb::Concat,
+        "split" => DyadicVerb::Split,
+        "join" => DyadicVerb::Join,
+        "substr" => DyadicVerb::Substring,
+        "contains" => DyadicVerb::Contains,
+        "startswith" => DyadicVerb::StartsWith,
+        "replace" => DyadicVerb::Replace,
+        "group" => DyadicVerb::Group,
+        "validate" => DyadicVerb::Validate,
+        "tojson" => DyadicVerb::ToJson,
+        "fromcsv" => DyadicVerb::FromCsv,
+        "readcsv" => DyadicVerb::ReadCsv,
+        "tocsv" => DyadicVerb::ToCsv,
+        "writecsv" => DyadicVerb::WriteCsv,
+        "writetext" => DyadicVerb::WriteText,
+        "appendtext" => DyadicVerb::AppendText,
+        "catch" => DyadicVerb::Catch,
         other => panic!("Dyadic Verb {:?} not implemented", other)
     }
 }
@@ -149,6 +281,32 @@ fn parse_monadic_verb(pair: pest::iterators::Pair<Rule>, rhs: AstNode) -> AstNod
             "print" => MonadicVerb::Print,
             "⍳" => MonadicVerb::Generate,
             "⍴" => MonadicVerb::Shape,
+            "≢" => MonadicVerb::Tally,
+            "⍉" => MonadicVerb::Transpose,
+            "isnull" => MonadicVerb::IsNull,
+            "tocolumns" => MonadicVerb::ToColumns,
+            "torecords" => MonadicVerb::ToRecords,
+            "rank" => MonadicVerb::Rank,
+            "denserank" => MonadicVerb::DenseRank,
+            "cumcount" => MonadicVerb::CumulativeCount,
+            "year" => MonadicVerb::Year,
+            "month" => MonadicVerb::Month,
+            "day" => MonadicVerb::Day,
+            "weekday" => MonadicVerb::Weekday,
+            "length" => MonadicVerb::Length,
+            "upper" => MonadicVerb::Upper,
+            "lower" => MonadicVerb::Lower,
+            "trim" => MonadicVerb::Trim,
+            "fromjson" => MonadicVerb::FromJson,
+            "readjson" => MonadicVerb::ReadJson,
+            "tojson" => MonadicVerb::ToJson,
+            "fromcsv" => MonadicVerb::FromCsv,
+            "readcsv" => MonadicVerb::ReadCsv,
+            "tocsv" => MonadicVerb::ToCsv,
+            "readtext" => MonadicVerb::ReadText,
+            "readlines" => MonadicVerb::ReadLines,
+            "exists" => MonadicVerb::Exists,
+            "listdir" => MonadicVerb::ListDir,
             other => panic!("Monadic Verb '{}' not implemented", other)
         }
     }
@@ -156,11 +314,81 @@ fn parse_monadic_verb(pair: pest::iterators::Pair<Rule>, rhs: AstNode) -> AstNod
 
 fn parse_operator_verb(lhs_verb_pair: pest::iterators::Pair<Rule>, operator_verb_pair: pest::iterators::Pair<Rule>, rhs: AstNode) -> AstNode {
     AstNode::OperatorOp {
-        lhs_verb: dyadic_verb_from_str(lhs_verb_pair.as_str()),
+        lhs_verb: dyadic_verb_from_pair(lhs_verb_pair),
         operator_verb: match operator_verb_pair.as_str() {
             "/" => OperatorVerb::Reduce,
+            "\\" => OperatorVerb::Scan,
             other => panic!("Operator Verb '{}' not implemented", other)
         },
         rhs: Box::new(rhs)
     }
-}
\ No newline at end of file
+}
+
+// Strip the quotes from a string literal and resolve its escapes, raw strings are kept as written
+fn parse_string_literal(literal: &str) -> String {
+    if let Some (raw) = literal.strip_prefix('r') {
+        return raw[1..raw.len() - 1].to_string();
+    }
+
+    let quote = literal.chars().next().unwrap();
+    let mut chars = literal[1..literal.len() - 1].chars();
+    let mut output = String::new();
+
+    while let Some (c) = chars.next() {
+        match c {
+            // A doubled quote is a single quote character
+            c if c == quote => {
+                chars.next();
+                output.push(quote);
+            },
+            '\\' => {
+                let escaped = match chars.next() {
+                    Some ('n') => '\n',
+                    Some ('t') => '\t',
+                    Some ('r') => '\r',
+                    Some ('0') => '\0',
+                    Some ('\\') => '\\',
+                    Some ('\'') => '\'',
+                    Some ('"') => '"',
+                    Some ('u') => parse_unicode_escape(&mut chars, literal),
+                    other => panic!("Unknown escape {:?} in string {}", other, literal)
+                };
+
+                output.push(escaped);
+            },
+            other => output.push(other)
+        }
+    }
+
+    output
+}
+
+// Read the hex code point of a \u{...} escape
+fn parse_unicode_escape(chars: &mut std::str::Chars, literal: &str) -> char {
+    if chars.next() != Some('{') {
+        panic!("Unicode escapes must be written \\u{{...}} in string {}", literal);
+    }
+
+    let hex: String = chars.by_ref().take_while(|c| *c != '}').collect();
+
+    match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+        Some (c) => c,
+        None => panic!("Invalid unicode escape \\u{{{}}} in string {}", hex, literal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_program;
+
+    #[test]
+    fn parses_reduce_and_scan_operators() {
+        assert!(parse_program("+/ [1, 2]\n×\\ [1, 2]\n3 +/ [1, 2, 3]").is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_operators() {
+        assert!(parse_program("+¨ [1, 2]").is_err());
+        assert!(parse_program("1 ¨ 2").is_err());
+    }
+}
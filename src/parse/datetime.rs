@@ -0,0 +1,314 @@
+use std::fmt;
+
+pub const SECONDS_PER_DAY: i64 = 86400;
+
+// Calendar date, stored as the number of days since 1970-01-01
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    days: i64
+}
+
+// Date and time of day without a timezone, stored as the number of seconds since 1970-01-01T00:00:00
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DateTime {
+    seconds: i64
+}
+
+// Length of time in seconds, which can be negative
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Duration {
+    seconds: i64
+}
+
+impl Date {
+    pub fn from_days(days: i64) -> Date {
+        Date { days }
+    }
+
+    // Parse a date written as YYYY-MM-DD
+    pub fn parse(date_str: &str) -> Date {
+        let parts: Option<Vec<i64>> = date_str.split('-').map(|x| x.parse().ok()).collect();
+
+        match parts.as_deref() {
+            Some ([year, month, day]) if is_valid_date(*year, *month, *day) => Date { days: days_from_civil(*year, *month, *day) },
+            _ => panic!("Invalid date {:?}", date_str)
+        }
+    }
+
+    pub fn days(self) -> i64 {
+        self.days
+    }
+
+    pub fn year(self) -> i64 {
+        civil_from_days(self.days).0
+    }
+
+    pub fn month(self) -> i64 {
+        civil_from_days(self.days).1
+    }
+
+    pub fn day(self) -> i64 {
+        civil_from_days(self.days).2
+    }
+
+    // ISO weekday, 1 is Monday through to 7 for Sunday
+    pub fn weekday(self) -> i64 {
+        // 1970-01-01 was a Thursday
+        (self.days + 3).rem_euclid(7) + 1
+    }
+}
+
+impl DateTime {
+    pub fn from_seconds(seconds: i64) -> DateTime {
+        DateTime { seconds }
+    }
+
+    pub fn from_date(date: Date) -> DateTime {
+        DateTime { seconds: date.days * SECONDS_PER_DAY }
+    }
+
+    // Parse a date time written as YYYY-MM-DDTHH:MM or YYYY-MM-DDTHH:MM:SS
+    pub fn parse(datetime_str: &str) -> DateTime {
+        let (date_str, time_str) = match datetime_str.split_once('T') {
+            Some (parts) => parts,
+            None => panic!("Invalid date time {:?}", datetime_str)
+        };
+
+        let date = Date::parse(date_str);
+        let time: Option<Vec<i64>> = time_str.split(':').map(|x| x.parse().ok()).collect();
+
+        let (hours, minutes, seconds) = match time.as_deref() {
+            Some ([hours, minutes]) => (*hours, *minutes, 0),
+            Some ([hours, minutes, seconds]) => (*hours, *minutes, *seconds),
+            _ => panic!("Invalid time {:?}", time_str)
+        };
+
+        if hours < 0 || minutes < 0 || seconds < 0 || hours > 23 || minutes > 59 || seconds > 59 {
+            panic!("Invalid time {:?}", time_str);
+        }
+
+        DateTime { seconds: date.days * SECONDS_PER_DAY + hours * 3600 + minutes * 60 + seconds }
+    }
+
+    pub fn seconds(self) -> i64 {
+        self.seconds
+    }
+
+    pub fn date(self) -> Date {
+        Date { days: self.seconds.div_euclid(SECONDS_PER_DAY) }
+    }
+
+    pub fn seconds_of_day(self) -> i64 {
+        self.seconds.rem_euclid(SECONDS_PER_DAY)
+    }
+}
+
+impl Duration {
+    pub fn from_seconds(seconds: i64) -> Duration {
+        Duration { seconds }
+    }
+
+    // Parse an ISO 8601 duration in weeks, days, hours, minutes and seconds, e.g. P1W2D or -PT1H30M.
+    // Years and months are not allowed as they don't have a fixed length. At least one part must be
+    // given, each a number followed by its designator, and a T must be followed by a time part
+    pub fn parse(duration_str: &str) -> Duration {
+        let (sign, unsigned) = match duration_str.strip_prefix('-') {
+            Some (rest) => (-1, rest),
+            None => (1, duration_str)
+        };
+
+        let designators = match unsigned.strip_prefix('P') {
+            Some (rest) => rest,
+            None => panic!("Invalid duration {:?}", duration_str)
+        };
+
+        let mut seconds: i64 = 0;
+        let mut number = String::new();
+        let mut in_time = false;
+        let mut parts = 0;
+        let mut time_parts = 0;
+
+        for c in designators.chars() {
+            let unit_seconds = match (c, in_time) {
+                ('T', false) => {
+                    in_time = true;
+                    continue;
+                },
+                (digit, _) if digit.is_ascii_digit() => {
+                    number.push(digit);
+                    continue;
+                },
+                ('W', false) => 7 * SECONDS_PER_DAY,
+                ('D', false) => SECONDS_PER_DAY,
+                ('H', true) => 3600,
+                ('M', true) => 60,
+                ('S', true) => 1,
+                (other, _) => panic!("Invalid designator {:?} in duration {:?}", other, duration_str)
+            };
+
+            let part_seconds = number.parse::<i64>().ok().and_then(|x| x.checked_mul(unit_seconds));
+
+            seconds = match part_seconds.and_then(|x| seconds.checked_add(x)) {
+                Some (total) => total,
+                None => panic!("Invalid duration {:?}", duration_str)
+            };
+
+            number.clear();
+            parts += 1;
+            time_parts += in_time as i32;
+        }
+
+        if parts == 0 || !number.is_empty() || (in_time && time_parts == 0) {
+            panic!("Invalid duration {:?}", duration_str);
+        }
+
+        Duration { seconds: sign * seconds }
+    }
+
+    pub fn seconds(self) -> i64 {
+        self.seconds
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn is_valid_date(year: i64, month: i64, day: i64) -> bool {
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => return false
+    };
+
+    day >= 1 && day <= days_in_month
+}
+
+// Days since 1970-01-01 of a date in the proleptic Gregorian calendar, see
+// http://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146097 + day_of_era - 719468
+}
+
+// Inverse of days_from_civil, giving (year, month, day)
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400;
+
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+// Written in ISO 8601 form, e.g. 2021-03-04
+impl fmt::Debug for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (year, month, day) = civil_from_days(self.days);
+
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)
+    }
+}
+
+// Written in ISO 8601 form, e.g. 2021-03-04T09:30:00
+impl fmt::Debug for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let time = self.seconds_of_day();
+
+        write!(f, "{:?}T{:02}:{:02}:{:02}", self.date(), time / 3600, (time % 3600) / 60, time % 60)
+    }
+}
+
+// Written in ISO 8601 form, e.g. P3DT4H30M
+impl fmt::Debug for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.seconds < 0 { "-" } else { "" };
+        let total = self.seconds.abs();
+
+        let (days, hours, minutes, seconds) = (total / SECONDS_PER_DAY, (total % SECONDS_PER_DAY) / 3600, (total % 3600) / 60, total % 60);
+
+        let mut out = format!("{}P", sign);
+
+        if days > 0 {
+            out.push_str(&format!("{}D", days));
+        }
+
+        if hours > 0 || minutes > 0 || seconds > 0 || days == 0 {
+            out.push('T');
+
+            if hours > 0 {
+                out.push_str(&format!("{}H", hours));
+            }
+
+            if minutes > 0 {
+                out.push_str(&format!("{}M", minutes));
+            }
+
+            if seconds > 0 || (hours == 0 && minutes == 0) {
+                out.push_str(&format!("{}S", seconds));
+            }
+        }
+
+        write!(f, "{}", out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Date, DateTime, Duration, SECONDS_PER_DAY};
+    use crate::parse::build_ast::parse_program;
+
+    #[test]
+    fn parses_dates_and_times() {
+        assert_eq!(Date::parse("1970-01-02").days(), 1);
+        assert_eq!(Date::parse("2024-02-29").weekday(), 4);
+        assert_eq!(DateTime::parse("1970-01-01T01:02:03").seconds(), 3723);
+        assert_eq!(DateTime::parse("1969-12-31T23:59").seconds(), -60);
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(Duration::parse("P1W2D").seconds(), 9 * SECONDS_PER_DAY);
+        assert_eq!(Duration::parse("-PT1H30M").seconds(), -5400);
+        assert_eq!(Duration::parse("P1DT1S").seconds(), SECONDS_PER_DAY + 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid date \"2021-ab-01\"")]
+    fn rejects_dates_that_are_not_numbers() {
+        Date::parse("2021-ab-01");
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid date \"2021-02-29\"")]
+    fn rejects_days_past_the_end_of_the_month() {
+        Date::parse("2021-02-29");
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid time \"9:xx\"")]
+    fn rejects_times_that_are_not_numbers() {
+        DateTime::parse("2021-01-01T9:xx");
+    }
+
+    #[test]
+    fn rejects_durations_without_parts() {
+        for invalid in ["P", "PT", "P1DT", "P1", "PTD", "P1H", "1D"] {
+            assert!(std::panic::catch_unwind(|| Duration::parse(invalid)).is_err(), "{}", invalid);
+        }
+
+        assert!(parse_program("@P").is_err());
+    }
+}
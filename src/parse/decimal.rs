@@ -0,0 +1,254 @@
+use num_bigint::{BigInt, Sign};
+use num_traits::{Zero, Signed, ToPrimitive};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Sub, Mul};
+
+// Exact fixed point decimal, the value is mantissa × 10^-scale e.g. 12.40 is 1240 at scale 2.
+// Addition, subtraction and multiplication are exact, division rounds to a given scale
+#[derive(Clone)]
+pub struct Decimal {
+    mantissa: BigInt,
+    scale: u32
+}
+
+// Decimal places kept by default when a division doesn't terminate, e.g. 1d ÷ 3 is 0.333333
+pub const DEFAULT_DIVIDE_SCALE: u32 = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    HalfEven,
+    HalfUp,
+    Down,
+    Up,
+    Floor,
+    Ceiling
+}
+
+impl Rounding {
+    pub fn from_name(name: &str) -> Option<Rounding> {
+        match name {
+            "half_even" => Some(Rounding::HalfEven),
+            "half_up" => Some(Rounding::HalfUp),
+            "down" => Some(Rounding::Down),
+            "up" => Some(Rounding::Up),
+            "floor" => Some(Rounding::Floor),
+            "ceiling" => Some(Rounding::Ceiling),
+            _ => None
+        }
+    }
+}
+
+impl Decimal {
+    pub fn from_big_int(x: BigInt) -> Decimal {
+        Decimal { mantissa: x, scale: 0 }
+    }
+
+    // Parse the digits of a decimal literal, e.g. -12.40
+    pub fn parse(digits: &str) -> Decimal {
+        let (whole, fraction) = match digits.find('.') {
+            Some (point) => (&digits[..point], &digits[point + 1..]),
+            None => (digits, "")
+        };
+
+        let mantissa: BigInt = format!("{}{}", whole, fraction).parse().unwrap();
+
+        Decimal { mantissa, scale: fraction.len() as u32 }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.mantissa.to_f64().unwrap_or(f64::NAN) / 10f64.powi(self.scale as i32)
+    }
+
+    // The mantissa when the value is written with a larger scale
+    fn mantissa_at(&self, scale: u32) -> BigInt {
+        &self.mantissa * BigInt::from(10).pow(scale - self.scale)
+    }
+
+    // Divide, rounding the result to the given number of decimal places
+    pub fn divide(&self, other: &Decimal, scale: u32, rounding: Rounding) -> Decimal {
+        if other.mantissa.is_zero() {
+            panic!("DOMAIN ERROR: cannot divide decimal {:?} by zero", self);
+        }
+
+        // (a × 10^-p) ÷ (b × 10^-q) at scale s is (a × 10^(s + q)) ÷ (b × 10^p)
+        let numerator = &self.mantissa * BigInt::from(10).pow(scale + other.scale);
+        let denominator = &other.mantissa * BigInt::from(10).pow(self.scale);
+
+        // Integer division truncates towards zero, decide whether to step away from zero instead
+        let quotient = &numerator / &denominator;
+        let remainder = &numerator % &denominator;
+
+        let negative = (numerator.sign() == Sign::Minus) != (denominator.sign() == Sign::Minus);
+        let twice_remainder: BigInt = remainder.abs() * 2;
+        let divisor = denominator.abs();
+
+        let away_from_zero = !remainder.is_zero() && match rounding {
+            Rounding::Down => false,
+            Rounding::Up => true,
+            Rounding::Floor => negative,
+            Rounding::Ceiling => !negative,
+            Rounding::HalfUp => twice_remainder >= divisor,
+            Rounding::HalfEven => match twice_remainder.cmp(&divisor) {
+                Ordering::Greater => true,
+                Ordering::Equal => (&quotient % 2u32) != BigInt::zero(),
+                Ordering::Less => false
+            }
+        };
+
+        let mantissa = match (away_from_zero, negative) {
+            (true, true) => quotient - 1,
+            (true, false) => quotient + 1,
+            (false, _) => quotient
+        };
+
+        Decimal { mantissa, scale }
+    }
+
+    // Divide without a scale to round to. A result that terminates within DEFAULT_DIVIDE_SCALE
+    // places is exact, keeping at least the places of either side e.g. 12.40 ÷ 2 is 6.20 and
+    // 3 ÷ 2 is 1.5. Otherwise it's rounded to that many places, or more if either side has more
+    pub fn divide_to_fit(&self, other: &Decimal, rounding: Rounding) -> Decimal {
+        let min_scale = self.scale.max(other.scale);
+        let mut result = self.divide(other, min_scale.max(DEFAULT_DIVIDE_SCALE), rounding);
+
+        if result.clone() * other.clone() == *self {
+            while result.scale > min_scale && (&result.mantissa % 10u32).is_zero() {
+                result.mantissa /= 10u32;
+                result.scale -= 1;
+            }
+        }
+
+        result
+    }
+}
+
+impl Add for Decimal {
+    type Output = Decimal;
+
+    fn add(self, other: Decimal) -> Decimal {
+        let scale = self.scale.max(other.scale);
+
+        Decimal { mantissa: self.mantissa_at(scale) + other.mantissa_at(scale), scale }
+    }
+}
+
+impl Sub for Decimal {
+    type Output = Decimal;
+
+    fn sub(self, other: Decimal) -> Decimal {
+        let scale = self.scale.max(other.scale);
+
+        Decimal { mantissa: self.mantissa_at(scale) - other.mantissa_at(scale), scale }
+    }
+}
+
+impl Mul for Decimal {
+    type Output = Decimal;
+
+    fn mul(self, other: Decimal) -> Decimal {
+        Decimal { mantissa: self.mantissa * other.mantissa, scale: self.scale + other.scale }
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Decimal) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Decimal) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Decimal) -> Ordering {
+        let scale = self.scale.max(other.scale);
+
+        self.mantissa_at(scale).cmp(&other.mantissa_at(scale))
+    }
+}
+
+// Written out in full e.g. 12.40, rather than as the mantissa and scale
+impl fmt::Debug for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = self.mantissa.abs().to_string();
+        let sign = if self.mantissa.sign() == Sign::Minus { "-" } else { "" };
+        let scale = self.scale as usize;
+
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - scale);
+
+        write!(f, "{}{}.{}", sign, whole, fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Decimal, Rounding};
+    use crate::execute::test_helpers::evaluate;
+
+    // Divide each of the values by 1 at scale 0 with the rounding mode, as MapLang literals
+    fn round_with(mode: &str) -> String {
+        evaluate(&format!("⎕scale <- 0\n⎕round <- '{}'\n[2.5d, 3.5d, -2.5d, 2.4d, 2.6d] ÷ 1", mode)).to_string()
+    }
+
+    #[test]
+    fn parses_literals() {
+        for digits in ["0", "12.40", "-0.05", "1300.00", "99999999999999999999999.1"] {
+            assert_eq!(format!("{:?}", Decimal::parse(digits)), digits);
+        }
+
+        assert_eq!(Decimal::parse("12.40"), Decimal::parse("12.4"));
+        assert_eq!(evaluate("12.40d").to_string(), "12.40d");
+    }
+
+    #[test]
+    fn adds_subtracts_and_multiplies_exactly() {
+        assert_eq!(format!("{:?}", Decimal::parse("0.1") + Decimal::parse("0.2")), "0.3");
+        assert_eq!(evaluate("0.1d + 0.2d").to_string(), "0.3d");
+        assert_eq!(evaluate("(0.1d + 0.2d) = 0.3d").to_string(), "1");
+        assert_eq!(evaluate("1d - 0.99d").to_string(), "0.01d");
+        assert_eq!(evaluate("-0.05d × 3").to_string(), "-0.15d");
+    }
+
+    #[test]
+    fn divides_exactly_where_the_result_terminates() {
+        assert_eq!(evaluate("(1d + 2d) ÷ 2").to_string(), "1.5d");
+        assert_eq!(evaluate("12.40d ÷ 2").to_string(), "6.20d");
+        assert_eq!(evaluate("(+/ [500.00d, 1000.00d, 2400.00d]) ÷ 3").to_string(), "1300.00d");
+        assert_eq!(evaluate("1d ÷ 3").to_string(), "0.333333d");
+        assert_eq!(format!("{:?}", Decimal::parse("2").divide_to_fit(&Decimal::parse("3"), Rounding::Floor)), "0.666666");
+    }
+
+    #[test]
+    fn rounds_with_each_mode() {
+        assert_eq!(round_with("half_even"), "[2d, 4d, -2d, 2d, 3d]");
+        assert_eq!(round_with("half_up"), "[3d, 4d, -3d, 2d, 3d]");
+        assert_eq!(round_with("down"), "[2d, 3d, -2d, 2d, 2d]");
+        assert_eq!(round_with("up"), "[3d, 4d, -3d, 3d, 3d]");
+        assert_eq!(round_with("floor"), "[2d, 3d, -3d, 2d, 2d]");
+        assert_eq!(round_with("ceiling"), "[3d, 4d, -2d, 3d, 3d]");
+    }
+
+    #[test]
+    fn rounds_to_the_scale() {
+        assert_eq!(evaluate("⎕scale <- 2\n1d ÷ 3").to_string(), "0.33d");
+        assert_eq!(evaluate("⎕scale <- 4\n(1d + 2d) ÷ 2").to_string(), "1.5000d");
+        assert_eq!(evaluate("⎕scale <- 0\n⎕round <- 'ceiling'\n10d ÷ 4").to_string(), "3d");
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown rounding mode")]
+    fn rejects_unknown_rounding_modes() {
+        round_with("nearest");
+    }
+}
@@ -1,2 +1,4 @@
 pub mod structures;
-pub mod build_ast;
\ No newline at end of file
+pub mod build_ast;
+pub mod decimal;
+pub mod datetime;
\ No newline at end of file
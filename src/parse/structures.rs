@@ -1,6 +1,13 @@
-use std::collections::HashMap;
-use std::ops::{Add, Mul};
+use indexmap::IndexMap;
+use std::ops::{Add, Sub, Mul, Div};
+use std::cmp::Ordering;
+use num_bigint::BigInt;
+use num_traits::{Zero, ToPrimitive};
+use super::decimal::{Decimal, Rounding};
+use super::datetime::{Date, DateTime, Duration};
 use std::iter::Sum;
+use crate::execute::overflow::check_overflow;
+use std::fmt;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum AstNode {
@@ -20,15 +27,104 @@ pub enum AstNode {
         operator_verb: OperatorVerb,
         rhs: Box<AstNode>
     },
+    DyadicOperatorOp {
+        lhs_verb: DyadicVerb,
+        operator_verb: OperatorVerb,
+        lhs: Box<AstNode>,
+        rhs: Box<AstNode>
+    },
     Terms(Vec<AstNode>),
     GlobalVar {
         variable: String,
         expression: Box<AstNode>,
     },
     Array(Vec<AstNode>),
-    Map(HashMap<String, AstNode>),
+    Map(IndexMap<MapKey, AstNode>),
+    String(String),
+    Date(Date),
+    DateTime(DateTime),
+    Duration(Duration),
+    Null,
+    Variable(String),
+    // Function written in braces, keeping its source so it can be printed
+    Function {
+        source: String,
+        body: Vec<AstNode>
+    },
+    Call {
+        function: Box<AstNode>,
+        args: Vec<AstNode>
+    },
+    Import {
+        path: String,
+        namespace: Option<String>
+    }
+}
+
+// Key of a map. Keys are usually strings, but ints, dates and composite keys of several values
+// (written as an array e.g. [2021, 'sales']) allow grouping on values other than names
+#[derive(PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+pub enum MapKey {
     String(String),
-    Variable(String)
+    Int(i64),
+    Date(Date),
+    Composite(Vec<MapKey>)
+}
+
+// Written as the key would be in a literal e.g. "name", 2021 or [2021, "sales"]
+impl fmt::Debug for MapKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapKey::String (key) => write!(f, "{:?}", key),
+            MapKey::Int (key) => write!(f, "{}", key),
+            MapKey::Date (key) => write!(f, "@{:?}", key),
+            MapKey::Composite (keys) => f.debug_list().entries(keys).finish()
+        }
+    }
+}
+
+// Written as MapLang source e.g. 'name', 2021, @2021-03-04 or [2021, 'sales']
+impl fmt::Display for MapKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapKey::String (key) => write!(f, "{}", string_literal(key)),
+            MapKey::Int (key) => write!(f, "{}", key),
+            MapKey::Date (key) => write!(f, "@{:?}", key),
+            MapKey::Composite (keys) => {
+                let keys: Vec<String> = keys.iter().map(|key| key.to_string()).collect();
+
+                write!(f, "[{}]", keys.join(", "))
+            }
+        }
+    }
+}
+
+// Single quoted string literal reading back as the same text, with quotes doubled and control
+// characters escaped e.g. 'it''s\n'
+pub fn string_literal(text: &str) -> String {
+    let mut literal = String::from("'");
+
+    for c in text.chars() {
+        match c {
+            '\'' => literal.push_str("''"),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\t' => literal.push_str("\\t"),
+            '\r' => literal.push_str("\\r"),
+            '\0' => literal.push_str("\\0"),
+            c if c.is_control() => literal.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => literal.push(c)
+        }
+    }
+
+    literal.push('\'');
+    literal
+}
+
+impl From<&str> for MapKey {
+    fn from(key: &str) -> MapKey {
+        MapKey::String(key.to_string())
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -38,27 +134,146 @@ pub enum DyadicVerb {
     Divide,
     Multiply,
     Equals,
+    NotEquals,
+    Member,
     Replicate,
+    Reshape,
     GreaterThan,
-    Access
+    Access,
+    Merge,
+    // Merge two maps, combining values under colliding keys with the given verb
+    MergeWith(Box<DyadicVerb>),
+    Intersect,
+    // Apply the given verb only over keys present in both maps
+    IntersectWith(Box<DyadicVerb>),
+    Left,
+    Right,
+    Fill,
+    Lag,
+    Lead,
+    MovingAverage,
+    Rank,
+    DenseRank,
+    CumulativeCount,
+    Concat,
+    Split,
+    Join,
+    Substring,
+    Contains,
+    StartsWith,
+    Replace,
+    Group,
+    Validate,
+    ToJson,
+    FromCsv,
+    ReadCsv,
+    ToCsv,
+    WriteCsv,
+    WriteText,
+    AppendText,
+    // Runs the lhs, giving the rhs instead if it raises an error
+    Catch
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum MonadicVerb {
     Print,
     Generate,
-    Shape
+    Shape,
+    Tally,
+    Transpose,
+    IsNull,
+    ToColumns,
+    ToRecords,
+    Rank,
+    DenseRank,
+    CumulativeCount,
+    Year,
+    Month,
+    Day,
+    Weekday,
+    Length,
+    Upper,
+    Lower,
+    Trim,
+    FromJson,
+    ReadJson,
+    ToJson,
+    FromCsv,
+    ReadCsv,
+    ToCsv,
+    ReadText,
+    ReadLines,
+    Exists,
+    ListDir
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum OperatorVerb {
-    Reduce
+    Reduce,
+    Scan
 }
 
-#[derive(Debug, Copy, PartialEq, Clone, PartialOrd)]
+// Operations on two ints give an int, and if either side is a float the int is promoted so the
+// result is a float. Division is the exception, giving an int only when ints divide exactly.
+// Ints that overflow 64 bits are promoted to big ints, and big ints are demoted back when they fit.
+// Exact decimals combined with ints stay exact decimals, but combined with floats give floats
+#[derive(Debug, Clone)]
 pub enum Numeric {
     Int(i64),
-    Float(f64)
+    Float(f64),
+    BigInt(BigInt),
+    Decimal(Decimal)
+}
+
+impl Numeric {
+    pub fn as_float(&self) -> f64 {
+        match self {
+            Numeric::Int(x) => *x as f64,
+            Numeric::Float(x) => *x,
+            Numeric::BigInt(x) => x.to_f64().unwrap_or(f64::NAN),
+            Numeric::Decimal(x) => x.to_f64()
+        }
+    }
+
+    // Whole numbers as a big int, or None for floats and decimals
+    fn as_big_int(&self) -> Option<BigInt> {
+        match self {
+            Numeric::Int(x) => Some(BigInt::from(*x)),
+            Numeric::BigInt(x) => Some(x.clone()),
+            Numeric::Float(_) | Numeric::Decimal(_) => None
+        }
+    }
+
+    // Exact values as a decimal, or None for floats
+    pub fn as_decimal(&self) -> Option<Decimal> {
+        match self {
+            Numeric::Decimal(x) => Some(x.clone()),
+            Numeric::Float(_) => None,
+            whole => whole.as_big_int().map(Decimal::from_big_int)
+        }
+    }
+
+    // Divide where at least one side is a decimal, rounding to the given scale or by default as
+    // Decimal::divide_to_fit does. Falls back to float division if either side is a float
+    pub fn divide_decimal(&self, other: &Numeric, scale: Option<u32>, rounding: Rounding) -> Numeric {
+        match (self.as_decimal(), other.as_decimal()) {
+            (Some (decimal_a), Some (decimal_b)) => {
+                Numeric::Decimal(match scale {
+                    Some (scale) => decimal_a.divide(&decimal_b, scale, rounding),
+                    None => decimal_a.divide_to_fit(&decimal_b, rounding)
+                })
+            },
+            _ => Numeric::Float(self.as_float() / other.as_float())
+        }
+    }
+
+    fn from_big_int(x: BigInt) -> Numeric {
+        match x.to_i64() {
+            Some (small) => Numeric::Int(small),
+            None => Numeric::BigInt(x)
+        }
+    }
 }
 
 impl Add for Numeric {
@@ -66,9 +281,31 @@ impl Add for Numeric {
 
     fn add(self, other: Numeric) -> Numeric {
         match (self, other) {
-            (Numeric::Int(a), Numeric::Int(b)) => Numeric::Int(a + b),
-            (Numeric::Float(a), Numeric::Float(b)) => Numeric::Float(a + b),
-            (self_unknown, other_unknown) => panic!("Cannot add numerics: {:?} + {:?}", self_unknown, other_unknown)
+            (Numeric::Int(a), Numeric::Int(b)) if a.checked_add(b).is_some() => Numeric::Int(a + b),
+            (a, b) => match (a.as_big_int(), b.as_big_int()) {
+                (Some (big_a), Some (big_b)) => Numeric::from_big_int(big_a + big_b),
+                _ => match (a.as_decimal(), b.as_decimal()) {
+                    (Some (decimal_a), Some (decimal_b)) => Numeric::Decimal(decimal_a + decimal_b),
+                    _ => Numeric::Float(a.as_float() + b.as_float())
+                }
+            }
+        }
+    }
+}
+
+impl Sub for Numeric {
+    type Output = Numeric;
+
+    fn sub(self, other: Numeric) -> Numeric {
+        match (self, other) {
+            (Numeric::Int(a), Numeric::Int(b)) if a.checked_sub(b).is_some() => Numeric::Int(a - b),
+            (a, b) => match (a.as_big_int(), b.as_big_int()) {
+                (Some (big_a), Some (big_b)) => Numeric::from_big_int(big_a - big_b),
+                _ => match (a.as_decimal(), b.as_decimal()) {
+                    (Some (decimal_a), Some (decimal_b)) => Numeric::Decimal(decimal_a - decimal_b),
+                    _ => Numeric::Float(a.as_float() - b.as_float())
+                }
+            }
         }
     }
 }
@@ -78,40 +315,111 @@ impl Mul for Numeric {
 
     fn mul(self, other: Numeric) -> Numeric {
         match (self, other) {
-            (Numeric::Int(a), Numeric::Int(b)) => Numeric::Int(a * b),
-            (Numeric::Float(a), Numeric::Float(b)) => Numeric::Float(a * b),
-            (self_unknown, other_unknown) => panic!("Cannot multiply numerics: {:?} + {:?}", self_unknown, other_unknown)
+            (Numeric::Int(a), Numeric::Int(b)) if a.checked_mul(b).is_some() => Numeric::Int(a * b),
+            (a, b) => match (a.as_big_int(), b.as_big_int()) {
+                (Some (big_a), Some (big_b)) => Numeric::from_big_int(big_a * big_b),
+                _ => match (a.as_decimal(), b.as_decimal()) {
+                    (Some (decimal_a), Some (decimal_b)) => Numeric::Decimal(decimal_a * decimal_b),
+                    _ => Numeric::Float(a.as_float() * b.as_float())
+                }
+            }
+        }
+    }
+}
+
+impl Div for Numeric {
+    type Output = Numeric;
+
+    fn div(self, other: Numeric) -> Numeric {
+        match (self.as_big_int(), other.as_big_int()) {
+            // Demote to an int when no precision is lost
+            (Some (big_a), Some (big_b)) if !big_b.is_zero() && (&big_a % &big_b).is_zero() => Numeric::from_big_int(big_a / big_b),
+            _ => match (&self, &other) {
+                // Decimals are exact where the result terminates, otherwise rounded
+                (Numeric::Decimal(_), _) | (_, Numeric::Decimal(_)) => self.divide_decimal(&other, None, Rounding::HalfEven),
+                _ => Numeric::Float(self.as_float() / other.as_float())
+            }
+        }
+    }
+}
+
+impl PartialEq for Numeric {
+    fn eq(&self, other: &Numeric) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Numeric {
+    fn partial_cmp(&self, other: &Numeric) -> Option<Ordering> {
+        match (self, other) {
+            (Numeric::Int(a), Numeric::Int(b)) => a.partial_cmp(b),
+            (a, b) => match (a.as_big_int(), b.as_big_int()) {
+                (Some (big_a), Some (big_b)) => big_a.partial_cmp(&big_b),
+                _ => match (a.as_decimal(), b.as_decimal()) {
+                    (Some (decimal_a), Some (decimal_b)) => decimal_a.partial_cmp(&decimal_b),
+                    _ => a.as_float().partial_cmp(&b.as_float())
+                }
+            }
         }
     }
 }
 
-impl<'a> Sum<Numeric> for Numeric {
+// Sums follow ⎕overflow like the dyadic verbs, checking each partial total
+impl Sum<Numeric> for Numeric {
     fn sum<I>(iter: I) -> Self
     where
         I: Iterator<Item = Numeric>, 
     {
-        let mut int_vals: Vec<i64> = Vec::new();
-        let mut float_vals: Vec<f64> = Vec::new();
+        let mut total: Option<Numeric> = None;
 
         for val in iter {
-            match val {
-                Numeric::Int (x) => int_vals.push(x),
-                Numeric::Float (x) => float_vals.push(x)
-            }
+            total = Some(match total {
+                Some (subtotal) => check_overflow(subtotal + val),
+                None => val
+            });
         }
 
-        if int_vals.len() > 0 && float_vals.len() > 0 {
-            panic!("Cannot fold Numeric with float and ints");
+        match total {
+            Some (total) => total,
+            None => panic!("Couldn't fold Numeric")
         }
+    }
+}
 
-        if int_vals.len() > 0 {
-            return Numeric::Int(int_vals.into_iter().sum());
+// Written as MapLang source, so floats always have a fraction e.g. 1300.0 and decimals a d suffix
+// e.g. 12.40d. Infinities and NaN have no literal and are written as null
+impl fmt::Display for Numeric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Numeric::Int (x) => write!(f, "{}", x),
+            Numeric::BigInt (x) => write!(f, "{}", x),
+            Numeric::Decimal (x) => write!(f, "{:?}d", x),
+            // Display never uses an exponent, which float literals can't have
+            Numeric::Float (x) if x.is_finite() && x.fract() == 0.0 => write!(f, "{}.0", x),
+            Numeric::Float (x) if x.is_finite() => write!(f, "{}", x),
+            Numeric::Float (_) => write!(f, "null")
         }
+    }
+}
 
-        if float_vals.len() > 0 {
-            return Numeric::Float(float_vals.into_iter().sum())
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execute::structures::ExecuteOutput;
+    use crate::execute::system_variables::set_system_variable;
 
-        panic!("Couldn't fold Numeric");
+    #[test]
+    fn sums_promote_overflowing_ints() {
+        let total: Numeric = vec![Numeric::Int(i64::MAX), Numeric::Int(1)].into_iter().sum();
+
+        assert_eq!(total, Numeric::BigInt(BigInt::from(i64::MAX) + 1));
     }
-}
\ No newline at end of file
+
+    #[test]
+    #[should_panic(expected = "DOMAIN ERROR")]
+    fn sums_follow_overflow_errors() {
+        set_system_variable("⎕overflow".to_string(), ExecuteOutput::String("error".to_string()));
+
+        let _: Numeric = vec![Numeric::Int(i64::MAX), Numeric::Int(1)].into_iter().sum();
+    }
+}